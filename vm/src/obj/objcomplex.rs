@@ -2,12 +2,14 @@ use num_complex::Complex64;
 use num_traits::Zero;
 
 use crate::function::OptionalArg;
+use crate::pyhash::PyHash;
 use crate::pyobject::{
     IntoPyObject, PyClassImpl, PyContext, PyObjectRef, PyRef, PyResult, PyValue,
 };
 use crate::vm::VirtualMachine;
 
 use super::objfloat::{self, PyFloat};
+use super::objstr;
 use super::objtype::{self, PyClassRef};
 
 /// Create a complex number from a real part and an optional imaginary part.
@@ -47,12 +49,341 @@ pub fn get_value(obj: &PyObjectRef) -> Complex64 {
 }
 
 fn try_complex(value: &PyObjectRef, vm: &VirtualMachine) -> PyResult<Option<Complex64>> {
-    Ok(if objtype::isinstance(&value, &vm.ctx.complex_type()) {
-        Some(get_value(&value))
-    } else if let Some(float) = objfloat::try_float(value, vm)? {
-        Some(Complex64::new(float, 0.0))
+    if objtype::isinstance(&value, &vm.ctx.complex_type()) {
+        return Ok(Some(get_value(&value)));
+    }
+    if let Some(float) = objfloat::try_float(value, vm)? {
+        return Ok(Some(Complex64::new(float, 0.0)));
+    }
+    // Mirrors how `try_float` falls back to `__float__`: a `__complex__` method lets a
+    // user-defined numeric class participate in complex arithmetic/construction the
+    // same way it already can via `__float__`.
+    if let Ok(method) = vm.get_attribute(value.clone(), "__complex__") {
+        let result = vm.invoke(&method, vec![])?;
+        return if objtype::isinstance(&result, &vm.ctx.complex_type()) {
+            Ok(Some(get_value(&result)))
+        } else {
+            Err(vm.new_type_error(format!(
+                "__complex__ returned non-complex (type {})",
+                result.class().name()
+            )))
+        };
+    }
+    Ok(None)
+}
+
+/// `try_complex`, but for `complex_new`'s `real`/`imag` arguments, which (unlike
+/// binary-operator operands) must raise rather than silently fall back to
+/// `NotImplemented` when they can't be coerced.
+fn to_complex_arg(value: &PyObjectRef, position: &str, vm: &VirtualMachine) -> PyResult<Complex64> {
+    try_complex(value, vm)?.ok_or_else(|| {
+        vm.new_type_error(format!(
+            "complex() {} argument must be a string or a number, not '{}'",
+            position,
+            value.class().name()
+        ))
+    })
+}
+
+/// Divides `a / b` using Smith's algorithm, which scales by the ratio of the
+/// divisor's components rather than its squared modulus - this avoids the spurious
+/// overflow/underflow a naive `(a * b.conj()) / b.norm_sqr()` would hit for operands
+/// whose components are large/small enough that squaring them overflows/underflows
+/// even though the true quotient is perfectly representable.
+fn complex_div(a: Complex64, b: Complex64) -> Complex64 {
+    if b.re.abs() >= b.im.abs() {
+        let ratio = b.im / b.re;
+        let denom = b.re + b.im * ratio;
+        Complex64::new((a.re + a.im * ratio) / denom, (a.im - a.re * ratio) / denom)
+    } else {
+        let ratio = b.re / b.im;
+        let denom = b.re * ratio + b.im;
+        Complex64::new((a.re * ratio + a.im) / denom, (a.im * ratio - a.re) / denom)
+    }
+}
+
+/// `base` raised to an integer power `n`, by repeated squaring on the complex value
+/// itself (CPython's `c_powi`) rather than going through the transcendental `log`/`exp`
+/// path `complex_pow` uses for non-integer exponents. Squaring is exact for e.g.
+/// `(0+1j)**2`, whereas the transcendental path would only produce a rounded
+/// approximation of `-1+0j`.
+fn complex_powi(base: Complex64, n: i64) -> Complex64 {
+    let mut exponent = n.unsigned_abs();
+    let mut result = Complex64::new(1.0, 0.0);
+    let mut factor = base;
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result *= factor;
+        }
+        factor *= factor;
+        exponent >>= 1;
+    }
+    if n < 0 {
+        complex_div(Complex64::new(1.0, 0.0), result)
+    } else {
+        result
+    }
+}
+
+/// `base ** exponent`, following CPython's `complexobject.c:c_pow`. A small integer
+/// exponent (`|n| <= 100`) with no imaginary part is handled exactly via
+/// `complex_powi`; everything else goes through the general transcendental form
+/// `len = |base|^re(exponent)`, `phase = atan2(base) * re(exponent)`, adjusted by the
+/// exponent's imaginary part when present.
+fn complex_pow(base: Complex64, exponent: Complex64, vm: &VirtualMachine) -> PyResult<Complex64> {
+    if exponent.is_zero() {
+        return Ok(Complex64::new(1.0, 0.0));
+    }
+    if base.is_zero() {
+        return if exponent.re < 0.0 || exponent.im != 0.0 {
+            Err(vm.new_zero_division_error("0.0 to a negative or complex power".to_string()))
+        } else {
+            Ok(Complex64::new(0.0, 0.0))
+        };
+    }
+    if exponent.im == 0.0 && exponent.re.fract() == 0.0 && exponent.re.abs() <= 100.0 {
+        return Ok(complex_powi(base, exponent.re as i64));
+    }
+
+    let vabs = base.re.hypot(base.im);
+    let at = base.im.atan2(base.re);
+    let mut len = vabs.powf(exponent.re);
+    let mut phase = at * exponent.re;
+    if exponent.im != 0.0 {
+        len /= (at * exponent.im).exp();
+        phase += exponent.im * vabs.ln();
+    }
+    Ok(Complex64::new(len * phase.cos(), len * phase.sin()))
+}
+
+/// Parses a `complex()`-style string such as `"1+2j"`, `"-j"`, or `" ( 1.5-2.5j ) "` into
+/// its `(real, imag)` parts, mirroring CPython's `complex_from_string`. Returns `None`
+/// on anything malformed, which the caller turns into `complex() arg is a malformed
+/// string`.
+fn parse_complex_str(s: &str) -> Option<(f64, f64)> {
+    let trimmed = s.trim();
+    let inner = match (trimmed.starts_with('('), trimmed.ends_with(')')) {
+        (true, true) => trimmed[1..trimmed.len() - 1].trim(),
+        (false, false) => trimmed,
+        _ => return None, // unmatched parenthesis
+    };
+    if inner.is_empty() {
+        return None;
+    }
+
+    if let Some(imag_part) = inner.strip_suffix('j').or_else(|| inner.strip_suffix('J')) {
+        // Find the `+`/`-` that separates the real and imaginary parts, scanning from
+        // the end and skipping a leading sign or one that's part of an exponent
+        // (`1e-10`), so the split only ever lands between the two components.
+        let indices: Vec<(usize, char)> = imag_part.char_indices().collect();
+        let split_at = (1..indices.len()).rev().find_map(|i| {
+            let (byte_pos, c) = indices[i];
+            let prev = indices[i - 1].1;
+            ((c == '+' || c == '-') && prev != 'e' && prev != 'E').then(|| byte_pos)
+        });
+        match split_at {
+            Some(byte_pos) => {
+                let real = imag_part[..byte_pos].parse::<f64>().ok()?;
+                let imag = parse_signed_unit_float(&imag_part[byte_pos..])?;
+                Some((real, imag))
+            }
+            None => Some((0.0, parse_signed_unit_float(imag_part)?)),
+        }
+    } else {
+        Some((inner.parse::<f64>().ok()?, 0.0))
+    }
+}
+
+/// Parses the imaginary-only forms `j`/`+j`/`-j` (read as `1j`/`+1j`/`-1j`) as well as
+/// ordinary float literals like `4` or `-2.5e3` for the imaginary part's coefficient.
+fn parse_signed_unit_float(s: &str) -> Option<f64> {
+    match s {
+        "" | "+" => Some(1.0),
+        "-" => Some(-1.0),
+        _ => s.parse::<f64>().ok(),
+    }
+}
+
+/// Renders one component (`re` or `im`) the way CPython's complex repr does: `inf`/
+/// `nan` in lowercase, and otherwise the shortest round-trippable digit string -
+/// notably *without* forcing a trailing `.0` onto whole numbers the way `repr(float)`
+/// does, since `repr(1+2j)` is `'(1+2j)'`, not `'(1.0+2.0j)'`. Rust's `Display` for
+/// `f64` already produces that shortest round-trippable form (and preserves the sign
+/// of zero), so this only needs to patch up `inf`/`nan` casing.
+fn format_complex_component(v: f64) -> String {
+    if v.is_nan() {
+        "nan".to_string()
+    } else if v.is_infinite() {
+        if v > 0.0 {
+            "inf".to_string()
+        } else {
+            "-inf".to_string()
+        }
+    } else {
+        format!("{}", v)
+    }
+}
+
+/// Builds the `(re+imj)` / `imj` body shared by `__repr__`/`__str__`/`__format__`: the
+/// real part and parentheses are dropped only when the real part is a *positive* zero
+/// (so `-0+2j` still shows its real part). In the parenthesized form the imaginary part
+/// always carries an explicit sign, since `format_component` only signs negative values;
+/// in the bare `imj` form it is signed only when negative (`2j`, `-2j`).
+fn format_complex_body(re: f64, im: f64, format_component: impl Fn(f64) -> String) -> String {
+    if re == 0.0 && !re.is_sign_negative() {
+        format!("{}j", format_component(im))
     } else {
+        let sign = if im.is_sign_negative() { "-" } else { "+" };
+        format!(
+            "({}{}{}j)",
+            format_component(re),
+            sign,
+            format_component(im.abs())
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct FloatFormatSpec {
+    fill_zero: bool,
+    width: Option<usize>,
+    precision: Option<usize>,
+    ty: char, // one of 'e', 'E', 'f', 'F', 'g', 'G', or '\0' for "unspecified"
+}
+
+/// Parses the subset of the standard format mini-language `__format__` advertises
+/// support for: `[0][width][.precision][type]`, where `type` is one of the float
+/// presentation types `e`/`E`/`f`/`F`/`g`/`G`. Returns `None` for anything else, which
+/// the caller turns into the usual `ValueError` for an invalid format spec.
+fn parse_float_format_spec(spec: &str) -> Option<FloatFormatSpec> {
+    let mut rest = spec;
+    let fill_zero = rest.starts_with('0');
+    if fill_zero {
+        rest = &rest[1..];
+    }
+
+    let width_digits: String = rest.chars().take_while(|c| c.is_ascii_digit()).collect();
+    rest = &rest[width_digits.len()..];
+    let width = if width_digits.is_empty() {
         None
+    } else {
+        width_digits.parse().ok()
+    };
+
+    let precision = if let Some(after_dot) = rest.strip_prefix('.') {
+        let digits: String = after_dot.chars().take_while(|c| c.is_ascii_digit()).collect();
+        rest = &after_dot[digits.len()..];
+        Some(digits.parse().ok()?)
+    } else {
+        None
+    };
+
+    let ty = match rest.chars().next() {
+        None => '\0',
+        Some(c @ ('e' | 'E' | 'f' | 'F' | 'g' | 'G')) => c,
+        Some(_) => return None,
+    };
+    if ty != '\0' && rest.chars().count() != 1 {
+        return None; // trailing garbage after the type character
+    }
+
+    Some(FloatFormatSpec {
+        fill_zero,
+        width,
+        precision,
+        ty,
+    })
+}
+
+/// Turns Rust's `{:e}` output (`"1.2e3"`, `"1.2e-3"`) into Python's (`"1.2e+03"`,
+/// `"1.2e-03"`): an explicit sign and at least two exponent digits.
+fn pythonize_exponent(s: &str, upper: bool) -> String {
+    match s.find(['e', 'E']) {
+        Some(e_pos) => {
+            let (mantissa, exp_part) = s.split_at(e_pos);
+            let digits = &exp_part[1..];
+            let (sign, digits) = match digits.strip_prefix('-') {
+                Some(d) => ("-", d),
+                None => ("+", digits),
+            };
+            let e_char = if upper { "E" } else { "e" };
+            if digits.len() < 2 {
+                format!("{}{}{}0{}", mantissa, e_char, sign, digits)
+            } else {
+                format!("{}{}{}{}", mantissa, e_char, sign, digits)
+            }
+        }
+        None => s.to_string(),
+    }
+}
+
+fn trim_trailing_zeros(s: &str) -> String {
+    if !s.contains('.') {
+        return s.to_string();
+    }
+    s.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+/// A simplified `%g`: fixed-point for exponents in `[-4, precision)`, scientific
+/// notation outside that range, trailing zeros trimmed either way - matches Python's
+/// general format closely enough for the ranges `__format__` is actually exercised on.
+fn format_general(v: f64, precision: usize) -> String {
+    if v == 0.0 {
+        return format_complex_component(v);
+    }
+    let precision = precision.max(1);
+    let exponent = v.abs().log10().floor() as i32;
+    if exponent < -4 || exponent >= precision as i32 {
+        let sci = format!("{:.*e}", precision - 1, v);
+        let (mantissa, exp_part) = sci.split_at(sci.find('e').unwrap_or(sci.len()));
+        pythonize_exponent(&format!("{}{}", trim_trailing_zeros(mantissa), exp_part), false)
+    } else {
+        let decimals = (precision as i32 - 1 - exponent).max(0) as usize;
+        trim_trailing_zeros(&format!("{:.*}", decimals, v))
+    }
+}
+
+/// Formats one component (`re` or `im`) per the standard float presentation types.
+fn format_float_component(v: f64, spec: &FloatFormatSpec) -> String {
+    if v.is_nan() || v.is_infinite() {
+        return format_complex_component(v);
+    }
+    match spec.ty {
+        'e' => pythonize_exponent(&format!("{:.*e}", spec.precision.unwrap_or(6), v), false),
+        'E' => pythonize_exponent(&format!("{:.*e}", spec.precision.unwrap_or(6), v), true),
+        'f' | 'F' => format!("{:.*}", spec.precision.unwrap_or(6), v),
+        'G' => format_general(v, spec.precision.unwrap_or(6)).to_uppercase(),
+        'g' => format_general(v, spec.precision.unwrap_or(6)),
+        // No type character: match `repr`'s component formatting, optionally
+        // overriding the number of significant digits if a precision was given.
+        _ => match spec.precision {
+            Some(p) => format_general(v, p),
+            None => format_complex_component(v),
+        },
+    }
+}
+
+/// Implements `complex.__format__` for a non-empty format spec: each component is
+/// formatted independently per `spec`, then joined as `re+imj` - unlike `repr`, a
+/// spec'd result is never parenthesized and the real component is never dropped, even
+/// when it's a positive zero (`format(2j, '.1f')` is `'0.0+2.0j'`, not `'2.0j'`) -
+/// and the whole result is padded out to `width` (CPython only right-aligns
+/// complex/float values, so alignment/fill beyond zero-padding isn't supported here).
+fn format_complex_spec(value: Complex64, spec_str: &str) -> Option<String> {
+    let spec = parse_float_format_spec(spec_str)?;
+    let re = format_float_component(value.re, &spec);
+    let sign = if value.im.is_sign_negative() { "-" } else { "+" };
+    let im = format_float_component(value.im.abs(), &spec);
+    let body = format!("{}{}{}j", re, sign, im);
+    Some(match spec.width {
+        Some(width) if body.chars().count() < width => {
+            let pad = if spec.fill_zero { '0' } else { ' ' };
+            let padding: String = std::iter::repeat(pad)
+                .take(width - body.chars().count())
+                .collect();
+            format!("{}{}", padding, body)
+        }
+        _ => body,
     })
 }
 
@@ -141,6 +472,63 @@ impl PyComplex {
         )
     }
 
+    #[pymethod(name = "__truediv__")]
+    fn truediv(&self, other: PyObjectRef, vm: &VirtualMachine) -> PyResult {
+        try_complex(&other, vm)?.map_or_else(
+            || Ok(vm.ctx.not_implemented()),
+            |other| {
+                if other.is_zero() {
+                    return Err(vm.new_zero_division_error("complex division by zero".to_string()));
+                }
+                complex_div(self.value, other).into_pyobject(vm)
+            },
+        )
+    }
+
+    #[pymethod(name = "__rtruediv__")]
+    fn rtruediv(&self, other: PyObjectRef, vm: &VirtualMachine) -> PyResult {
+        try_complex(&other, vm)?.map_or_else(
+            || Ok(vm.ctx.not_implemented()),
+            |other| {
+                if self.value.is_zero() {
+                    return Err(vm.new_zero_division_error("complex division by zero".to_string()));
+                }
+                complex_div(other, self.value).into_pyobject(vm)
+            },
+        )
+    }
+
+    #[pymethod(name = "__floordiv__")]
+    fn floordiv(&self, _other: PyObjectRef, vm: &VirtualMachine) -> PyResult {
+        Err(vm.new_type_error(String::from("can't take floor of complex number.")))
+    }
+
+    #[pymethod(name = "__mod__")]
+    fn mod_(&self, _other: PyObjectRef, vm: &VirtualMachine) -> PyResult {
+        Err(vm.new_type_error(String::from("can't mod complex numbers.")))
+    }
+
+    #[pymethod(name = "__divmod__")]
+    fn divmod(&self, _other: PyObjectRef, vm: &VirtualMachine) -> PyResult {
+        Err(vm.new_type_error(String::from("can't take floor or mod of complex number.")))
+    }
+
+    #[pymethod(name = "__pow__")]
+    fn pow(&self, other: PyObjectRef, vm: &VirtualMachine) -> PyResult {
+        try_complex(&other, vm)?.map_or_else(
+            || Ok(vm.ctx.not_implemented()),
+            |other| complex_pow(self.value, other, vm)?.into_pyobject(vm),
+        )
+    }
+
+    #[pymethod(name = "__rpow__")]
+    fn rpow(&self, other: PyObjectRef, vm: &VirtualMachine) -> PyResult {
+        try_complex(&other, vm)?.map_or_else(
+            || Ok(vm.ctx.not_implemented()),
+            |other| complex_pow(other, self.value, vm)?.into_pyobject(vm),
+        )
+    }
+
     #[pymethod(name = "__neg__")]
     fn neg(&self, _vm: &VirtualMachine) -> Complex64 {
         -self.value
@@ -149,10 +537,41 @@ impl PyComplex {
     #[pymethod(name = "__repr__")]
     fn repr(&self, _vm: &VirtualMachine) -> String {
         let Complex64 { re, im } = self.value;
-        if re == 0.0 {
-            format!("{}j", im)
+        format_complex_body(re, im, format_complex_component)
+    }
+
+    #[pymethod(name = "__str__")]
+    fn str(&self, vm: &VirtualMachine) -> String {
+        self.repr(vm)
+    }
+
+    #[pymethod(name = "__format__")]
+    fn format(&self, spec: PyObjectRef, vm: &VirtualMachine) -> PyResult<String> {
+        let spec_str = objstr::get_value(&spec);
+        if spec_str.is_empty() {
+            return Ok(self.repr(vm));
+        }
+        format_complex_spec(self.value, &spec_str).ok_or_else(|| {
+            vm.new_value_error(format!(
+                "Invalid format specifier '{}' for object of type 'complex'",
+                spec_str
+            ))
+        })
+    }
+
+    #[pymethod(name = "__hash__")]
+    fn hash(&self, _vm: &VirtualMachine) -> PyHash {
+        // Mirrors CPython's `complex_hash`: combine the components' float hashes the
+        // same way `hash(1j)` is derived, so `hash(a+0j) == hash(a)` for any real `a`
+        // while the imaginary part still participates whenever it's non-zero.
+        const PY_HASH_IMAG: PyHash = 1_000_003;
+        let re_hash = objfloat::hash_float(self.value.re);
+        let im_hash = objfloat::hash_float(self.value.im);
+        let combined = re_hash.wrapping_add(im_hash.wrapping_mul(PY_HASH_IMAG));
+        if combined == -1 {
+            -2
         } else {
-            format!("({}+{}j)", re, im)
+            combined
         }
     }
 
@@ -168,17 +587,42 @@ impl PyComplex {
         imag: OptionalArg<PyObjectRef>,
         vm: &VirtualMachine,
     ) -> PyResult<PyComplexRef> {
-        let real = match real {
-            OptionalArg::Missing => 0.0,
-            OptionalArg::Present(ref value) => objfloat::make_float(vm, value)?,
+        // `complex("1+2j")`: CPython parses the string itself rather than funneling it
+        // through `float()`, and refuses a second argument alongside it.
+        if let OptionalArg::Present(ref value) = real {
+            if objtype::isinstance(value, &vm.ctx.str_type()) {
+                if let OptionalArg::Present(_) = imag {
+                    return Err(vm.new_type_error(
+                        "complex() can't take second arg if first is a string".to_string(),
+                    ));
+                }
+                let (re, im) = parse_complex_str(&objstr::get_value(value)).ok_or_else(|| {
+                    vm.new_value_error("complex() arg is a malformed string".to_string())
+                })?;
+                return PyComplex {
+                    value: Complex64::new(re, im),
+                }
+                .into_ref_with_type(vm, cls);
+            }
+        }
+
+        // `real` may itself be a complex (or `__complex__`-coercible) value with a
+        // non-zero imaginary part, so both arguments go through `try_complex` and are
+        // combined the way CPython's `complex_new` does:
+        // `complex(a, b) == a + b*1j == (a.real - b.imag) + (a.imag + b.real)j`.
+        let real = match &real {
+            OptionalArg::Missing => Complex64::new(0.0, 0.0),
+            OptionalArg::Present(value) => to_complex_arg(value, "first", vm)?,
         };
 
-        let imag = match imag {
-            OptionalArg::Missing => 0.0,
-            OptionalArg::Present(ref value) => objfloat::make_float(vm, value)?,
+        let value = match &imag {
+            OptionalArg::Missing => real,
+            OptionalArg::Present(value) => {
+                let imag = to_complex_arg(value, "second", vm)?;
+                Complex64::new(real.re - imag.im, real.im + imag.re)
+            }
         };
 
-        let value = Complex64::new(real, imag);
         PyComplex { value }.into_ref_with_type(vm, cls)
     }
 }
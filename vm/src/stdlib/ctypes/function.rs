@@ -26,14 +26,482 @@ use std::collections::HashMap;
 
 // https://github.com/python/cpython/blob/4f8bb3947cfbc20f970ff9d9531e1132a9e95396/Modules/_ctypes/callproc.c#L15
 
+// `PyCFuncPtr._flags_` bit layout, mirroring CPython's Modules/_ctypes/ctypes.h. Only
+// FUNCFLAG_USE_ERRNO and FUNCFLAG_USE_LASTERROR are consulted here; FUNCFLAG_CDECL is the
+// value every function starts with, FUNCFLAG_PYTHONAPI is defined but not read, and any
+// other bits a caller sets are ignored.
+pub const FUNCFLAG_CDECL: u32 = 0x1;
+pub const FUNCFLAG_PYTHONAPI: u32 = 0x4;
+pub const FUNCFLAG_USE_ERRNO: u32 = 0x8;
+pub const FUNCFLAG_USE_LASTERROR: u32 = 0x10;
+
+/// Derives the `(use_errno, use_last_error)` booleans `ErrnoSwapGuard` wants from a raw
+/// `_flags_` bitmask. Used by `PyCFuncPtr`'s constructor, which may receive the flags as a
+/// bitmask; the CDLL path (`new_for_dll_with_errno`) already has the booleans.
+fn errno_flags(flags: u32) -> (bool, bool) {
+    (
+        flags & FUNCFLAG_USE_ERRNO != 0,
+        flags & FUNCFLAG_USE_LASTERROR != 0,
+    )
+}
+
+/// The calling convention a bare `CFUNCTYPE`/`CFuncPtr` should use when nothing more
+/// specific (`WinDLL`-style construction, which hard-codes `Stdcall`) says otherwise -
+/// i.e. "however the platform's C compiler calls a function with no special
+/// attributes". On 64-bit Windows that's the distinct `Win64` convention, but
+/// everywhere else - including 64-bit System V targets (Linux/macOS/*BSD) and 32-bit
+/// x86 - libffi's `ffi_prep_cif` only accepts `FFI_DEFAULT_ABI` for the platform's
+/// normal convention; `Sysv`/`Cdecl` are distinct, narrower ABI enum values (e.g.
+/// 32-bit `cdecl` specifically) that `ffi_prep_cif` rejects outside their own arch, so
+/// using either unconditionally would break `Cif` construction on the primary
+/// Linux/x86-64 target.
+pub(super) fn default_c_abi() -> Abi {
+    #[cfg(all(target_arch = "x86_64", target_os = "windows"))]
+    {
+        Abi::Win64
+    }
+    #[cfg(not(all(target_arch = "x86_64", target_os = "windows")))]
+    {
+        Abi::Default
+    }
+}
+
+// ctypes' private per-thread errno/last-error cells. `use_errno=True`/`use_last_error=True`
+// functions swap the OS value into these around the libffi call so that RustPython-internal
+// syscalls between the Python-level call and the user reading `get_errno()` can't clobber it.
+thread_local! {
+    static CTYPES_ERRNO: std::cell::Cell<i32> = std::cell::Cell::new(0);
+    static CTYPES_LAST_ERROR: std::cell::Cell<u32> = std::cell::Cell::new(0);
+}
+
+#[cfg(unix)]
+unsafe fn os_errno() -> i32 {
+    *libc::__errno_location()
+}
+
+#[cfg(unix)]
+unsafe fn set_os_errno(value: i32) {
+    *libc::__errno_location() = value;
+}
+
+#[cfg(windows)]
+unsafe fn os_last_error() -> u32 {
+    winapi::um::errhandlingapi::GetLastError()
+}
+
+#[cfg(windows)]
+unsafe fn set_os_last_error(value: u32) {
+    winapi::um::errhandlingapi::SetLastError(value)
+}
+
+/// RAII guard that, for the duration of a foreign call, installs ctypes' private errno
+/// (and/or last-error on Windows) as the OS-visible value, then saves whatever the C
+/// function left there back into the private cell when dropped. Must bracket only the
+/// libffi call itself, never the surrounding Python-level dispatch.
+struct ErrnoSwapGuard {
+    use_errno: bool,
+    use_last_error: bool,
+}
+
+impl ErrnoSwapGuard {
+    fn enter(use_errno: bool, use_last_error: bool) -> Self {
+        #[cfg(unix)]
+        if use_errno {
+            unsafe { set_os_errno(CTYPES_ERRNO.with(|c| c.get())) };
+        }
+        #[cfg(windows)]
+        if use_last_error {
+            unsafe { set_os_last_error(CTYPES_LAST_ERROR.with(|c| c.get())) };
+        }
+        let _ = use_last_error;
+        let _ = use_errno;
+        ErrnoSwapGuard { use_errno, use_last_error }
+    }
+}
+
+impl Drop for ErrnoSwapGuard {
+    fn drop(&mut self) {
+        #[cfg(unix)]
+        if self.use_errno {
+            CTYPES_ERRNO.with(|c| c.set(unsafe { os_errno() }));
+        }
+        #[cfg(windows)]
+        if self.use_last_error {
+            CTYPES_LAST_ERROR.with(|c| c.set(unsafe { os_last_error() }));
+        }
+    }
+}
+
+pub fn get_errno(_vm: &VirtualMachine) -> i32 {
+    CTYPES_ERRNO.with(|c| c.get())
+}
+
+pub fn set_errno(value: i32, _vm: &VirtualMachine) -> i32 {
+    CTYPES_ERRNO.with(|c| c.replace(value))
+}
+
+pub fn get_last_error(_vm: &VirtualMachine) -> u32 {
+    CTYPES_LAST_ERROR.with(|c| c.get())
+}
+
+pub fn set_last_error(value: u32, _vm: &VirtualMachine) -> u32 {
+    CTYPES_LAST_ERROR.with(|c| c.replace(value))
+}
+
+// --- Aggregate (Structure/Union) layout -----------------------------------
+//
+// Neither `ffi_type_from_str` nor `libffi::middle::Type` can tell us field
+// offsets directly, so rather than reverse-engineering them out of the
+// `Type` libffi builds, we mirror the same layout rules a C compiler uses
+// (natural alignment, no packing) ourselves: each field's offset is rounded
+// up to its own alignment, and the aggregate's total size is rounded up to
+// its widest member's alignment. This tree has no dedicated
+// `Structure`/`Union` base classes yet, so any ctypes type exposing a
+// `_fields_` sequence of `(name, type)` pairs is treated as an aggregate;
+// unions are opted into with a truthy class-level `_is_union_` attribute
+// rather than a real base-class check.
+
+fn scalar_size_align(type_char: &str) -> Option<(usize, usize)> {
+    match type_char {
+        "b" | "B" | "?" | "c" => Some((1, 1)),
+        "h" | "H" => Some((2, 2)),
+        "i" | "I" | "l" | "L" | "f" => Some((4, 4)),
+        "q" | "Q" | "d" | "P" | "z" | "Z" => Some((8, 8)),
+        _ => None,
+    }
+}
+
+fn fields_list(fields_obj: &PyObjectRef, vm: &VirtualMachine) -> PyResult<Vec<(String, PyObjectRef)>> {
+    let entries: Vec<PyObjectRef> = if let Some(t) = fields_obj.downcast_ref::<PyTuple>() {
+        t.as_slice().to_vec()
+    } else if let Some(l) = fields_obj.payload::<PyList>() {
+        l.borrow_vec().to_vec()
+    } else {
+        return Err(vm.new_type_error("_fields_ must be a list or tuple".to_string()));
+    };
+
+    entries
+        .into_iter()
+        .map(|entry| {
+            let pair = entry
+                .downcast_ref::<PyTuple>()
+                .map(|t| t.as_slice().to_vec())
+                .ok_or_else(|| vm.new_type_error("_fields_ entries must be (name, type) tuples".to_string()))?;
+            let name = pair
+                .get(0)
+                .and_then(|n| n.downcast_ref::<PyStr>())
+                .ok_or_else(|| vm.new_type_error("_fields_ entry name must be a string".to_string()))?
+                .as_str()
+                .to_owned();
+            let field_type = pair
+                .get(1)
+                .cloned()
+                .ok_or_else(|| vm.new_type_error("_fields_ entry missing a type".to_string()))?;
+            Ok((name, field_type))
+        })
+        .collect()
+}
+
+fn is_union(ctype_obj: &PyObjectRef, vm: &VirtualMachine) -> bool {
+    ctype_obj
+        .get_attr("_is_union_", vm)
+        .ok()
+        .map(|v| vm.is_true(&v).unwrap_or(false))
+        .unwrap_or(false)
+}
+
+/// Size and alignment of a ctypes type object (an instance or its class),
+/// recursing through arrays (`_length_`/`_type_`) and aggregates
+/// (`_fields_`) down to scalar `_type_` codes.
+fn size_align_of(ctype_obj: &PyObjectRef, vm: &VirtualMachine) -> PyResult<(usize, usize)> {
+    if let Ok(fields_obj) = ctype_obj.get_attr("_fields_", vm) {
+        let fields = fields_list(&fields_obj, vm)?;
+        let union = is_union(ctype_obj, vm);
+        let mut offset = 0usize;
+        let mut max_align = 1usize;
+        let mut max_size = 0usize;
+        for (_name, field_type) in &fields {
+            let (size, align) = size_align_of(field_type, vm)?;
+            max_align = max_align.max(align);
+            if union {
+                max_size = max_size.max(size);
+            } else {
+                offset = (offset + align - 1) / align * align;
+                offset += size;
+                max_size = offset;
+            }
+        }
+        let total = (max_size + max_align - 1) / max_align * max_align;
+        return Ok((total.max(1), max_align));
+    }
+    if let Ok(length_obj) = ctype_obj.get_attr("_length_", vm) {
+        let elem_type = ctype_obj.get_attr("_type_", vm)?;
+        let (elem_size, elem_align) = size_align_of(&elem_type, vm)?;
+        let length = length_obj
+            .downcast_ref::<crate::builtins::PyInt>()
+            .and_then(|i| i.as_bigint().to_usize())
+            .ok_or_else(|| vm.new_type_error("_length_ must be a non-negative int".to_string()))?;
+        return Ok((elem_size * length, elem_align));
+    }
+    let type_char_obj = ctype_obj
+        .get_attr("_type_", vm)
+        .map_err(|_| vm.new_type_error(format!("ctypes type {:?} has neither _fields_, _length_ nor _type_", ctype_obj)))?;
+    let type_char_str = type_char_obj
+        .downcast_ref::<PyStr>()
+        .ok_or_else(|| vm.new_type_error("_type_ attribute must be a string".to_string()))?;
+    scalar_size_align(type_char_str.as_str())
+        .ok_or_else(|| vm.new_type_error(format!("Invalid _type_ string '{}'", type_char_str.as_str())))
+}
+
+/// `(field name, byte offset, field type object)` for each member of an
+/// aggregate, using the same layout rules as `size_align_of`.
+fn aggregate_field_layout(
+    ctype_obj: &PyObjectRef,
+    fields_obj: &PyObjectRef,
+    vm: &VirtualMachine,
+) -> PyResult<Vec<(String, usize, PyObjectRef)>> {
+    let fields = fields_list(fields_obj, vm)?;
+    let union = is_union(ctype_obj, vm);
+    let mut offset = 0usize;
+    let mut layout = Vec::with_capacity(fields.len());
+    for (name, field_type) in fields {
+        let (size, align) = size_align_of(&field_type, vm)?;
+        let field_offset = if union {
+            0
+        } else {
+            offset = (offset + align - 1) / align * align;
+            offset
+        };
+        layout.push((name, field_offset, field_type.clone()));
+        if !union {
+            offset = field_offset + size;
+        }
+    }
+    Ok(layout)
+}
+
+/// Recursively resolves a ctypes type object to the `libffi::middle::Type`
+/// needed to build a `Cif`. Arrays are expanded to N repeated elements of
+/// the element type - `middle::Type` has no dedicated array constructor,
+/// but a structure of N identical members has the size/alignment layout
+/// libffi needs for aggregate marshalling, which is all we use it for.
+/// Unions are approximated as a one-member structure of the widest member
+/// (by size), since `middle::Type` likewise has no union constructor. The
+/// alignment is that of the widest member, which is smaller than the true
+/// union's when a narrower member is more strictly aligned.
+fn resolve_ffi_type(ctype_obj: &PyObjectRef, vm: &VirtualMachine) -> PyResult<Type> {
+    if let Ok(fields_obj) = ctype_obj.get_attr("_fields_", vm) {
+        let fields = fields_list(&fields_obj, vm)?;
+        if is_union(ctype_obj, vm) {
+            let mut widest: Option<(usize, Type)> = None;
+            for (_name, field_type) in &fields {
+                let (size, _align) = size_align_of(field_type, vm)?;
+                let ty = resolve_ffi_type(field_type, vm)?;
+                if widest.as_ref().map_or(true, |(best, _)| size > *best) {
+                    widest = Some((size, ty));
+                }
+            }
+            return Ok(Type::structure(vec![widest.map(|(_, t)| t).unwrap_or_else(Type::void)]));
+        }
+        let element_types = fields
+            .iter()
+            .map(|(_name, field_type)| resolve_ffi_type(field_type, vm))
+            .collect::<PyResult<Vec<Type>>>()?;
+        return Ok(Type::structure(element_types));
+    }
+    if let Ok(length_obj) = ctype_obj.get_attr("_length_", vm) {
+        let elem_type_obj = ctype_obj.get_attr("_type_", vm)?;
+        let elem_type = resolve_ffi_type(&elem_type_obj, vm)?;
+        let length = length_obj
+            .downcast_ref::<crate::builtins::PyInt>()
+            .and_then(|i| i.as_bigint().to_usize())
+            .ok_or_else(|| vm.new_type_error("_length_ must be a non-negative int".to_string()))?;
+        return Ok(Type::structure(std::iter::repeat(elem_type).take(length)));
+    }
+    let type_char_obj = ctype_obj
+        .get_attr("_type_", vm)
+        .map_err(|_| vm.new_type_error(format!("ctypes type {:?} has neither _fields_, _length_ nor _type_", ctype_obj)))?;
+    let type_char_str = type_char_obj
+        .downcast_ref::<PyStr>()
+        .ok_or_else(|| vm.new_type_error("_type_ attribute must be a string".to_string()))?;
+    ffi_type_from_str(type_char_str.as_str())
+        .ok_or_else(|| vm.new_type_error(format!("Invalid _type_ string '{}'", type_char_str.as_str())))
+}
+
+/// Runs a single `_argtypes_` element's `from_param` protocol against a
+/// runtime argument, mirroring CPython's `PyCFuncPtr` call path: prefer an
+/// explicit `from_param` classmethod on the declared type. With none
+/// available, a value `Function::call` already knows how to marshal
+/// (bytes/bytearrays/strings/`None`/ctypes instances, or anything with a
+/// `value` attribute) is passed through untouched, and any other value -
+/// e.g. a bare int or float - is coerced by constructing an instance of the
+/// declared type (e.g. `c_int(5)`).
+fn from_param(argtype_obj: &PyObjectRef, value: PyObjectRef, vm: &VirtualMachine) -> PyResult<PyObjectRef> {
+    if let Ok(from_param_fn) = argtype_obj.get_attr("from_param", vm) {
+        if from_param_fn.is_callable(vm) {
+            return vm.invoke(&from_param_fn, (value,));
+        }
+    }
+    let already_recognized = value.payload_if_subclass::<PyCSimple>(vm).is_some()
+        || value.payload_if_subclass::<PyCArray>(vm).is_some()
+        || value.get_attr("_fields_", vm).is_ok()
+        || value.payload::<crate::builtins::PyBytes>().is_some()
+        || value.payload::<crate::builtins::PyByteArray>().is_some()
+        || value.downcast_ref::<PyStr>().is_some()
+        || vm.is_none(&value)
+        || value.get_attr("value", vm).is_ok();
+    if already_recognized {
+        Ok(value)
+    } else {
+        argtype_obj.clone().call((value,), vm)
+    }
+}
+
+/// Writes `value` into the `size_align_of(field_type)` bytes at `dst`, the way a
+/// Structure/Union field (or array element) needs to be marshalled into its parent's
+/// buffer. `resolve_ffi_type` already builds the correct nested `libffi::middle::Type`
+/// for a field that is itself a Structure/Union or array, so libffi expects real bytes
+/// there too - recurse through `_fields_`/`_length_` the same way `aggregate_field_layout`
+/// does, instead of falling through to `write_pyobject_to_result_slot`'s scalar-only
+/// path (which would silently zero-fill anything without a scalar `_type_`).
+unsafe fn write_scalar_into_buffer(field_type: &PyObjectRef, value: &PyObjectRef, dst: *mut u8, vm: &VirtualMachine) -> PyResult<()> {
+    if let Ok(fields_obj) = field_type.get_attr("_fields_", vm) {
+        for (name, offset, nested_field_type) in aggregate_field_layout(field_type, &fields_obj, vm)? {
+            let nested_value = value.get_attr(name.as_str(), vm)?;
+            write_scalar_into_buffer(&nested_field_type, &nested_value, dst.add(offset), vm)?;
+        }
+        return Ok(());
+    }
+    if let Ok(length_obj) = field_type.get_attr("_length_", vm) {
+        let elem_type = field_type.get_attr("_type_", vm)?;
+        let (elem_size, _align) = size_align_of(&elem_type, vm)?;
+        let length = length_obj
+            .downcast_ref::<crate::builtins::PyInt>()
+            .and_then(|i| i.as_bigint().to_usize())
+            .ok_or_else(|| vm.new_value_error("_length_ must be a non-negative int".to_string()))?;
+        for i in 0..length {
+            let elem_value = vm.call_method(value, "__getitem__", (vm.ctx.new_int(i),))?;
+            write_scalar_into_buffer(&elem_type, &elem_value, dst.add(i * elem_size), vm)?;
+        }
+        return Ok(());
+    }
+    write_pyobject_to_result_slot(field_type, value, &mut *(dst as *mut c_void), vm);
+    Ok(())
+}
+
+/// Reads a ctypes value out of the `size_align_of(field_type)` bytes at `src`, the
+/// counterpart to `write_scalar_into_buffer`: recurses through nested Structure/Union
+/// (`_fields_`) and array (`_length_`/`_type_`) fields, constructing and populating an
+/// instance of `field_type`, rather than falling back to `raw_slot_to_pyobject`'s `None`
+/// for anything that isn't a scalar `_type_`.
+unsafe fn read_scalar_from_buffer(field_type: &PyObjectRef, src: *const u8, vm: &VirtualMachine) -> PyResult<PyObjectRef> {
+    if let Ok(fields_obj) = field_type.get_attr("_fields_", vm) {
+        let instance = field_type.clone().call((), vm)?;
+        for (name, offset, nested_field_type) in aggregate_field_layout(field_type, &fields_obj, vm)? {
+            let value = read_scalar_from_buffer(&nested_field_type, src.add(offset), vm)?;
+            instance.set_attr(name.as_str(), value, vm)?;
+        }
+        return Ok(instance);
+    }
+    if let Ok(length_obj) = field_type.get_attr("_length_", vm) {
+        let elem_type = field_type.get_attr("_type_", vm)?;
+        let (elem_size, _align) = size_align_of(&elem_type, vm)?;
+        let length = length_obj
+            .downcast_ref::<crate::builtins::PyInt>()
+            .and_then(|i| i.as_bigint().to_usize())
+            .ok_or_else(|| vm.new_value_error("_length_ must be a non-negative int".to_string()))?;
+        let instance = field_type.clone().call((), vm)?;
+        for i in 0..length {
+            let value = read_scalar_from_buffer(&elem_type, src.add(i * elem_size), vm)?;
+            vm.call_method(&instance, "__setitem__", (vm.ctx.new_int(i), value))?;
+        }
+        return Ok(instance);
+    }
+    Ok(raw_slot_to_pyobject(field_type, src as *const c_void, vm))
+}
+
+// C's default-argument-promotion rules, applied to the trailing arguments of
+// a variadic call (`_argtypes_` only covers the fixed prefix, same as a C
+// prototype's `...`). Used both as the `libffi::middle::Type` to build a Cif
+// with and as a cache key, since each distinct trailing-type signature needs
+// its own prepared (variadic) Cif.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum PromotedType {
+    CInt,
+    CLongLong,
+    CDouble,
+    CVoidP,
+}
+
+impl PromotedType {
+    fn to_ffi_type(&self) -> Type {
+        match self {
+            PromotedType::CInt => Type::c_int(),
+            PromotedType::CLongLong => Type::c_longlong(),
+            PromotedType::CDouble => Type::c_double(),
+            PromotedType::CVoidP => Type::pointer(),
+        }
+    }
+}
+
+/// Promotes a runtime Python argument to the C type `...` would give it:
+/// ints widen to `int` (or `long long` if they don't fit), floats widen to
+/// `double`, and anything pointer-shaped (bytes/str/None/a ctypes
+/// pointer-like object) stays a pointer.
+fn promote_variadic_arg(value: &PyObjectRef, vm: &VirtualMachine) -> PyResult<PromotedType> {
+    if let Some(int_val) = value.downcast_ref::<crate::builtins::PyInt>() {
+        return Ok(if int_val.as_bigint().to_i32().is_some() {
+            PromotedType::CInt
+        } else {
+            PromotedType::CLongLong
+        });
+    }
+    if value.downcast_ref::<crate::builtins::PyFloat>().is_some() {
+        return Ok(PromotedType::CDouble);
+    }
+    if value.downcast_ref::<PyStr>().is_some()
+        || value.payload::<crate::builtins::PyBytes>().is_some()
+        || value.payload::<crate::builtins::PyByteArray>().is_some()
+        || vm.is_none(value)
+        || value.get_attr("value", vm).is_ok()
+    {
+        return Ok(PromotedType::CVoidP);
+    }
+    Err(vm.new_type_error(format!(
+        "don't know how to promote a {:?} for a variadic argument",
+        value.class().name()
+    )))
+}
+
+// Identity (not value) comparison: catches `_restype_`/`_argtypes_` being
+// reassigned to a different object, which is the only signal we have that a
+// cached `Function` is stale.
+fn option_pyobj_is(cached: &Option<PyObjectRef>, current: &Option<PyObjectRef>) -> bool {
+    match (cached, current) {
+        (Some(a), Some(b)) => a.is(b),
+        (None, None) => true,
+        _ => false,
+    }
+}
+
 #[derive(Debug)]
 pub struct Function {
     args: Vec<Type>,
+    // The declared `_argtypes_` themselves (parallel to `args`), kept around
+    // so `call` can run each argument through its declared type's
+    // `from_param` protocol before marshalling it, the same way
+    // `original_restype` is kept around for the result side.
+    original_argtypes: Vec<PyObjectRef>,
     pointer: CodePtr,
     cif: Cif,
-    ffi_return_type: Type, 
-    original_restype: Option<PyObjectRef>, 
-    abi: Abi, 
+    ffi_return_type: Type,
+    original_restype: Option<PyObjectRef>,
+    abi: Abi,
+    // Extra Cifs for variadic calls, one per distinct trailing-argument
+    // promoted-type signature seen so far, so repeated calls with the same
+    // shape of trailing arguments (the overwhelmingly common case) don't
+    // rebuild a Cif every time.
+    variadic_cifs: PyRwLock<HashMap<Vec<PromotedType>, Cif>>,
 }
 
 unsafe impl Send for Function {}
@@ -50,33 +518,20 @@ impl Function {
         abi: Abi, 
         vm: &VirtualMachine,
     ) -> PyResult<Self> {
-        let ffi_arg_types: Vec<Type> = match argtypes_opt {
-            Some(argtypes_tuple_obj) if argtypes_tuple_obj.is_instance(&vm.ctx.types.tuple_type, vm) => {
-                let argtypes_tuple = argtypes_tuple_obj.downcast_ref::<PyTuple>().unwrap();
-                argtypes_tuple.iter().map(|ctypes_type_obj| {
-                    // Attempt to get _type_ attribute, assuming it's a PyCSimpleType or compatible PyCData subtype
-                    // This is a simplified version. Real implementation needs to handle various ctypes types
-                    // (pointers, arrays, structures, etc.) and might involve calling a method like `_get_ffi_type_`
-                    // on the ctypes_type_obj.
-                    let type_char_obj = ctypes_type_obj.get_attr("_type_", vm)
-                        .map_err(|_| vm.new_type_error(format!("argtype {:?} does not have a _type_ attribute", ctypes_type_obj)))?;
-                    let type_char_str = type_char_obj.downcast_ref::<PyStr>()
-                        .ok_or_else(|| vm.new_type_error(format!("_type_ attribute of argtype {:?} must be a string", ctypes_type_obj)))?;
-                    
-                    ffi_type_from_str(type_char_str.as_str())
-                        .ok_or_else(|| vm.new_type_error(format!("Invalid _type_ string '{}' in argtypes", type_char_str.as_str())))
-                }).collect::<PyResult<Vec<Type>>>()?
+        let (ffi_arg_types, original_argtypes): (Vec<Type>, Vec<PyObjectRef>) = match argtypes_opt {
+            Some(ref obj) if vm.is_none(obj) => (vec![], vec![]), // _argtypes_ is explicitly None
+            Some(ref obj) if obj.is_instance(&vm.ctx.types.tuple_type, vm) => {
+                let argtypes_tuple = obj.downcast_ref::<PyTuple>().unwrap();
+                // `resolve_ffi_type` handles simple `_type_` codes as well as
+                // arrays and Structure/Union aggregates (`_fields_`).
+                let ffi_types = argtypes_tuple
+                    .iter()
+                    .map(|ctypes_type_obj| resolve_ffi_type(ctypes_type_obj, vm))
+                    .collect::<PyResult<Vec<Type>>>()?;
+                (ffi_types, argtypes_tuple.as_slice().to_vec())
             }
-            Some(_) if vm.is_none(&argtypes_tuple_obj) => { // _argtypes_ is explicitly None
-                // Default behavior when _argtypes_ is None (e.g. could mean function is variadic, or infer from call)
-                // For now, let's require _argtypes_ to be a tuple if provided and not None.
-                // Or, if we want to support calling without _argtypes_ set (like original PoC):
-                 vec![] // This would mean Cif::new might fail or use a default if not variadic
-            }
-            None => { // _argtypes_ field was None itself
-                 vec![] // As above, Cif will be prepared with no specific arg types from Python side.
-            }
-            _ => { // _argtypes_ was set to something other than a tuple or None
+            None => (vec![], vec![]), // _argtypes_ field was never set
+            Some(_) => { // _argtypes_ was set to something other than a tuple or None
                 return Err(vm.new_type_error(
                     "_argtypes_ must be a tuple of ctypes types or None.".to_string()
                 ));
@@ -93,17 +548,14 @@ impl Function {
         let code_ptr = CodePtr(*pointer as *mut _);
 
         let (determined_ffi_return_type, stored_original_restype) = match restype_obj.as_ref() {
-            None | Some(obj) if vm.is_none(obj) => (Type::void(), None), // restype is None
+            None => (Type::void(), None), // restype not given at all
+            Some(obj) if vm.is_none(obj) => (Type::void(), None), // restype explicitly set to None
             Some(obj) => {
                 if let Ok(py_type) = obj.clone().downcast::<PyTypeRef>() { // It's a PyTypeRef
                     if py_type.is_subclass(PyCData::class(&vm.ctx).as_ref(), vm) { // And a ctypes type
-                        let type_char_obj = py_type.get_attr("_type_", vm)
-                            .map_err(|_| vm.new_type_error(format!("ctypes type {:?} as restype does not have a _type_ attribute", py_type)))?;
-                        let type_char_str = type_char_obj.downcast_ref::<PyStr>()
-                            .ok_or_else(|| vm.new_type_error(format!("_type_ attribute of restype {:?} must be a string", py_type)))?;
-                        
-                        let ffi_type = ffi_type_from_str(type_char_str.as_str())
-                            .ok_or_else(|| vm.new_type_error(format!("Invalid _type_ string '{}' in restype", type_char_str.as_str())))?;
+                        // Covers simple `_type_` codes as well as
+                        // Structure/Union aggregates via `_fields_`.
+                        let ffi_type = resolve_ffi_type(obj, vm)?;
                         (ffi_type, Some(obj.clone()))
                     } else if obj.is_callable(vm) { // A non-ctypes PyTypeRef that is callable (e.g. type itself if it's a callable type)
                         (Type::c_int(), Some(obj.clone()))
@@ -122,49 +574,280 @@ impl Function {
             }
         };
         
-        let cif = Cif::new(ffi_arg_types.clone(), determined_ffi_return_type.clone());
+        let mut cif = Cif::new(ffi_arg_types.clone(), determined_ffi_return_type.clone());
+        cif.set_abi(abi);
         Ok(Function {
             args: ffi_arg_types,
+            original_argtypes,
             cif,
             pointer: code_ptr,
             ffi_return_type: determined_ffi_return_type,
             original_restype: stored_original_restype,
+            abi,
+            variadic_cifs: PyRwLock::new(HashMap::new()),
         })
     }
 
     pub unsafe fn call(
         &self,
         args: Vec<PyObjectRef>, // These are Python arguments passed to the function
+        use_errno: bool,
+        use_last_error: bool,
         vm: &VirtualMachine,
     ) -> PyResult<PyObjectRef> {
-        let mut ffi_args = Vec::with_capacity(self.args.len());
-        if args.len() != self.args.len() {
+        // Fewer runtime arguments than `_argtypes_` declares is always
+        // wrong; more is only wrong if the function isn't variadic, which
+        // we don't know ahead of time, so the trailing-argument promotion
+        // below is what actually decides whether extras are acceptable.
+        if args.len() < self.args.len() {
             return Err(vm.new_type_error(format!(
-                "Expected {} arguments (based on _argtypes_), got {}",
+                "Expected at least {} arguments (based on _argtypes_), got {}",
                 self.args.len(),
                 args.len()
             )));
         }
 
+        // Run each argument covered by `_argtypes_` through its declared
+        // type's `from_param` protocol before any of the marshalling below
+        // sees it - mirrors CPython's ctypes, which always calls
+        // `argtype.from_param(value)` first and marshals whatever that
+        // returns. Trailing variadic arguments (past `_argtypes_`'s length)
+        // have no declared type to convert through, so they pass through
+        // unchanged.
+        let args: Vec<PyObjectRef> = args
+            .into_iter()
+            .enumerate()
+            .map(|(i, value)| match self.original_argtypes.get(i) {
+                Some(argtype_obj) => from_param(argtype_obj, value, vm),
+                None => Ok(value),
+            })
+            .collect::<PyResult<Vec<PyObjectRef>>>()?;
+
+        let mut ffi_args = Vec::with_capacity(args.len());
+
+        // Structure/Union arguments are passed by value: libffi wants a
+        // pointer to `size` bytes laid out per `aggregate_field_layout`, so
+        // we build that buffer up front. All buffers are pushed here, in
+        // order, before any `Arg` below borrows into them - interleaving
+        // the two would mean growing `aggregate_buffers` while an `Arg`
+        // still held a reference into one of its earlier elements. This
+        // (and the pointer-ish scratch right after) only covers the fixed
+        // prefix described by `_argtypes_` - trailing variadic arguments are
+        // handled separately below, after the fixed-prefix loop.
+        let mut aggregate_buffers: Vec<Vec<u8>> = Vec::new();
+        for py_arg in &args[..self.args.len()] {
+            if let Ok(fields_obj) = py_arg.get_attr("_fields_", vm) {
+                let (total_size, _align) = size_align_of(py_arg, vm)?;
+                let mut buffer = vec![0u8; total_size.max(1)];
+                for (name, offset, field_type) in aggregate_field_layout(py_arg, &fields_obj, vm)? {
+                    let value = py_arg.get_attr(name.as_str(), vm)?;
+                    write_scalar_into_buffer(&field_type, &value, buffer.as_mut_ptr().add(offset), vm)?;
+                }
+                aggregate_buffers.push(buffer);
+            }
+        }
+
+        // Pointer-ish arguments (`bytes`/`bytearray` for `char*`, `str`
+        // materialized into a `CString`, `None` as a null pointer, and
+        // ctypes pointer/`c_void_p`/`byref()` results that expose their
+        // address via a `value` attribute) all need somewhere stable to
+        // live: libffi only borrows the pointer during `cif.call`, but that
+        // call happens after this loop, so the owned buffer - and, for
+        // pointer-typed args, the pointer *value* itself - has to outlive
+        // it. We build it all up front for the same reason as the
+        // aggregate buffers above: growing this `Vec` while an earlier
+        // `Arg` still borrowed into one of its elements would be unsound.
+        enum ArgScratch {
+            CStr(CString, *const std::os::raw::c_char),
+            Bytes(Vec<u8>, *const u8),
+            Null(*const c_void),
+            Address(*const c_void),
+            Unsupported,
+        }
+        let mut arg_scratch: Vec<ArgScratch> = Vec::new();
+        for py_arg in &args[..self.args.len()] {
+            let handled_above = py_arg.payload_if_subclass::<PyCSimple>(vm).is_some()
+                || py_arg.payload_if_subclass::<PyCArray>(vm).is_some()
+                || py_arg.get_attr("_fields_", vm).is_ok();
+            if handled_above {
+                continue;
+            }
+            let scratch = if let Some(bytes_val) = py_arg.payload::<crate::builtins::PyBytes>() {
+                let buf = bytes_val.as_bytes().to_vec();
+                let ptr = buf.as_ptr();
+                ArgScratch::Bytes(buf, ptr)
+            } else if let Some(bytearray_val) = py_arg.payload::<crate::builtins::PyByteArray>() {
+                let buf = bytearray_val.borrow_vec().to_vec();
+                let ptr = buf.as_ptr();
+                ArgScratch::Bytes(buf, ptr)
+            } else if let Some(str_val) = py_arg.downcast_ref::<PyStr>() {
+                let cstring = CString::new(str_val.as_str())
+                    .map_err(|_| vm.new_value_error("embedded null byte in string argument".to_string()))?;
+                let ptr = cstring.as_ptr();
+                ArgScratch::CStr(cstring, ptr)
+            } else if vm.is_none(py_arg) {
+                ArgScratch::Null(ptr::null())
+            } else if let Ok(value_obj) = py_arg.get_attr("value", vm) {
+                // A ctypes pointer-like object (c_void_p, a `byref()`
+                // result, ...): pass the address it stores rather than the
+                // Python wrapper itself.
+                let addr = value_obj
+                    .downcast_ref::<crate::builtins::PyInt>()
+                    .and_then(|i| i.as_bigint().to_usize())
+                    .unwrap_or(0);
+                ArgScratch::Address(addr as *const c_void)
+            } else {
+                ArgScratch::Unsupported
+            };
+            arg_scratch.push(scratch);
+        }
+
+        let mut aggregate_cursor = 0usize;
+        let mut scratch_cursor = 0usize;
         for (py_arg, ffi_type_expected) in args.iter().zip(self.args.iter()) {
             // Argument conversion logic - Placeholder, needs robust implementation
             if let Some(simple_arg) = py_arg.payload_if_subclass::<PyCSimple>(vm) {
                  ffi_args.push(simple_arg.to_arg(ffi_type_expected.clone(), vm)?);
             } else if let Some(array_arg) = py_arg.payload_if_subclass::<PyCArray>(vm) {
                  ffi_args.push(array_arg.to_arg(vm)?);
+            } else if py_arg.get_attr("_fields_", vm).is_ok() {
+                // Structure/Union by value - pass a pointer to the buffer we
+                // already built above; libffi copies `size` bytes from it.
+                ffi_args.push(Arg::new(&aggregate_buffers[aggregate_cursor][0]));
+                aggregate_cursor += 1;
+            } else {
+                match &arg_scratch[scratch_cursor] {
+                    ArgScratch::CStr(_owned, ptr) => ffi_args.push(Arg::new(ptr)),
+                    ArgScratch::Bytes(_owned, ptr) => ffi_args.push(Arg::new(ptr)),
+                    ArgScratch::Null(ptr) => ffi_args.push(Arg::new(ptr)),
+                    ArgScratch::Address(ptr) => ffi_args.push(Arg::new(ptr)),
+                    ArgScratch::Unsupported => {
+                        return Err(vm.new_type_error(format!(
+                            "Argument type {:?} not yet supported for FFI call to convert to {:?}",
+                            py_arg.class().name(), ffi_type_expected
+                        )));
+                    }
+                }
+                scratch_cursor += 1;
             }
-            // TODO: Add more types like pointers, String/Bytes for char*, etc.
-            else {
-                return Err(vm.new_type_error(format!(
-                    "Argument type {:?} not yet supported for FFI call to convert to {:?}",
-                    py_arg.class().name(), ffi_type_expected
-                )));
+        }
+
+        // Trailing arguments beyond `_argtypes_`'s declared length: this is
+        // a variadic call (`printf`-style), so promote each one per C's
+        // default-argument-promotion rules and route it to a Cif prepared
+        // specifically for this trailing-type signature.
+        let mut variadic_key: Option<Vec<PromotedType>> = None;
+        enum VariadicScratch {
+            CStr(CString, *const std::os::raw::c_char),
+            Bytes(Vec<u8>, *const u8),
+            Null(*const c_void),
+            Address(*const c_void),
+            Int(i32),
+            LongLong(i64),
+            Double(f64),
+        }
+        // Must outlive the `cif.call` below, same as `aggregate_buffers` and
+        // `arg_scratch` above - declared here (rather than inside the `if`)
+        // so it isn't dropped before the call happens.
+        let mut trailing_scratch: Vec<VariadicScratch> = Vec::new();
+        if args.len() > self.args.len() {
+            let mut key = Vec::with_capacity(args.len() - self.args.len());
+            for py_arg in &args[self.args.len()..] {
+                let promoted = promote_variadic_arg(py_arg, vm)?;
+                let scratch = match promoted {
+                    PromotedType::CInt => VariadicScratch::Int(
+                        py_arg.downcast_ref::<crate::builtins::PyInt>().and_then(|i| i.as_bigint().to_i32()).unwrap_or(0),
+                    ),
+                    PromotedType::CLongLong => VariadicScratch::LongLong(
+                        py_arg.downcast_ref::<crate::builtins::PyInt>().and_then(|i| i.as_bigint().to_i64()).unwrap_or(0),
+                    ),
+                    PromotedType::CDouble => VariadicScratch::Double(
+                        py_arg.downcast_ref::<crate::builtins::PyFloat>().map(|f| f.to_f64()).unwrap_or(0.0),
+                    ),
+                    PromotedType::CVoidP => {
+                        if let Some(bytes_val) = py_arg.payload::<crate::builtins::PyBytes>() {
+                            let buf = bytes_val.as_bytes().to_vec();
+                            let ptr = buf.as_ptr();
+                            VariadicScratch::Bytes(buf, ptr)
+                        } else if let Some(bytearray_val) = py_arg.payload::<crate::builtins::PyByteArray>() {
+                            let buf = bytearray_val.borrow_vec().to_vec();
+                            let ptr = buf.as_ptr();
+                            VariadicScratch::Bytes(buf, ptr)
+                        } else if let Some(str_val) = py_arg.downcast_ref::<PyStr>() {
+                            let cstring = CString::new(str_val.as_str())
+                                .map_err(|_| vm.new_value_error("embedded null byte in string argument".to_string()))?;
+                            let ptr = cstring.as_ptr();
+                            VariadicScratch::CStr(cstring, ptr)
+                        } else if vm.is_none(py_arg) {
+                            VariadicScratch::Null(ptr::null())
+                        } else {
+                            let addr = py_arg
+                                .get_attr("value", vm)
+                                .ok()
+                                .and_then(|v| v.downcast_ref::<crate::builtins::PyInt>().and_then(|i| i.as_bigint().to_usize()))
+                                .unwrap_or(0);
+                            VariadicScratch::Address(addr as *const c_void)
+                        }
+                    }
+                };
+                key.push(promoted);
+                trailing_scratch.push(scratch);
+            }
+            for scratch in &trailing_scratch {
+                match scratch {
+                    VariadicScratch::CStr(_owned, ptr) => ffi_args.push(Arg::new(ptr)),
+                    VariadicScratch::Bytes(_owned, ptr) => ffi_args.push(Arg::new(ptr)),
+                    VariadicScratch::Null(ptr) => ffi_args.push(Arg::new(ptr)),
+                    VariadicScratch::Address(ptr) => ffi_args.push(Arg::new(ptr)),
+                    VariadicScratch::Int(v) => ffi_args.push(Arg::new(v)),
+                    VariadicScratch::LongLong(v) => ffi_args.push(Arg::new(v)),
+                    VariadicScratch::Double(v) => ffi_args.push(Arg::new(v)),
+                }
             }
+            variadic_key = Some(key);
         }
-        
+
+        // Picks the Cif to call with: the one prepared at `load` time for
+        // the plain, fixed-arity case, or - for a variadic call - one
+        // prepared (and cached) for this exact trailing-type signature.
+        enum CifRef<'a> {
+            Fixed(&'a Cif),
+            Variadic(rustpython_common::lock::PyRwLockReadGuard<'a, HashMap<Vec<PromotedType>, Cif>>, Vec<PromotedType>),
+        }
+        impl<'a> CifRef<'a> {
+            fn get(&self) -> &Cif {
+                match self {
+                    CifRef::Fixed(c) => c,
+                    CifRef::Variadic(guard, key) => guard.get(key).expect("just inserted"),
+                }
+            }
+        }
+        let cif_ref = if let Some(key) = variadic_key {
+            if !self.variadic_cifs.read().contains_key(&key) {
+                let mut full_types = self.args.clone();
+                full_types.extend(key.iter().map(PromotedType::to_ffi_type));
+                let mut new_cif = Cif::new_variadic(full_types, self.args.len(), self.ffi_return_type.clone());
+                new_cif.set_abi(self.abi);
+                self.variadic_cifs.write().insert(key.clone(), new_cif);
+            }
+            CifRef::Variadic(self.variadic_cifs.read(), key)
+        } else {
+            CifRef::Fixed(&self.cif)
+        };
+        let cif = cif_ref.get();
+
+        // `ErrnoSwapGuard` must bracket only the libffi call itself, never any VM work
+        // that runs after it (result-object construction, `set_attr`, ...) - that work
+        // can make RustPython-internal syscalls that would clobber the real `errno`
+        // before the guard's `Drop` gets a chance to save it back. So every arm below
+        // enters the guard only after its restype inspection, captures the raw
+        // `cif.call` result and drops the guard immediately after, before converting
+        // that raw result into a `PyObjectRef`.
         let result_val = match self.original_restype.as_ref() {
             None => { // Corresponds to Type::void() or restype explicitly set to None
-                self.cif.call::<()>(self.pointer, &ffi_args);
+                let errno_guard = ErrnoSwapGuard::enter(use_errno, use_last_error);
+                cif.call::<()>(self.pointer, &ffi_args);
+                drop(errno_guard);
                 vm.ctx.none()
             }
             Some(original_restype_obj) => {
@@ -172,30 +855,83 @@ impl Function {
                     py_type.is_subclass(PyCData::class(&vm.ctx).as_ref(), vm)
                 } else { false };
 
-                if is_ctypes_type {
+                if let Ok(fields_obj) = original_restype_obj.get_attr("_fields_", vm) {
+                    // Structure/Union return by value.
+                    let (total_size, _align) = size_align_of(original_restype_obj, vm)?;
+                    if total_size > 16 {
+                        // `middle::Cif::call`'s generic return type has to be a
+                        // fixed, compile-time size; anything that doesn't fit
+                        // in a u128 would need libffi's low-level raw call
+                        // API (writing straight into an arbitrarily-sized
+                        // result buffer), which this tree doesn't wire up yet.
+                        return Err(vm.new_not_implemented_error(format!(
+                            "struct/union return values larger than 16 bytes ({} bytes here) aren't supported yet",
+                            total_size
+                        )));
+                    }
+                    let errno_guard = ErrnoSwapGuard::enter(use_errno, use_last_error);
+                    let raw: u128 = cif.call(self.pointer, &ffi_args);
+                    drop(errno_guard);
+                    let buffer = raw.to_ne_bytes();
+                    let instance = original_restype_obj.clone().call((), vm)?;
+                    for (name, offset, field_type) in aggregate_field_layout(original_restype_obj, &fields_obj, vm)? {
+                        let value = read_scalar_from_buffer(&field_type, buffer.as_ptr().add(offset), vm)?;
+                        instance.set_attr(name.as_str(), value, vm)?;
+                    }
+                    instance
+                } else if is_ctypes_type {
                     // Assume simple types for now, based on _type_ char.
                     // More complex types (pointers, structures) would need more handling here.
                     let type_char_obj = original_restype_obj.get_attr("_type_", vm)?;
                     let type_char = type_char_obj.downcast_ref::<PyStr>().unwrap().as_str();
 
-                    match type_char {
-                        "i" | "l" => vm.ctx.new_int(self.cif.call::<i32>(self.pointer, &ffi_args)).into(),
-                        "I" | "L" => vm.ctx.new_int(self.cif.call::<u32>(self.pointer, &ffi_args)).into(),
-                        "q" => vm.ctx.new_int(self.cif.call::<i64>(self.pointer, &ffi_args)).into(),
-                        "Q" => vm.ctx.new_int(self.cif.call::<u64>(self.pointer, &ffi_args)).into(),
-                        "b" => vm.ctx.new_int(self.cif.call::<i8>(self.pointer, &ffi_args)).into(),
-                        "B" => vm.ctx.new_int(self.cif.call::<u8>(self.pointer, &ffi_args)).into(),
-                        "h" => vm.ctx.new_int(self.cif.call::<i16>(self.pointer, &ffi_args)).into(),
-                        "H" => vm.ctx.new_int(self.cif.call::<u16>(self.pointer, &ffi_args)).into(),
-                        "f" => vm.ctx.new_float(self.cif.call::<f32>(self.pointer, &ffi_args) as f64).into(),
-                        "d" => vm.ctx.new_float(self.cif.call::<f64>(self.pointer, &ffi_args)).into(),
-                        "?" => vm.ctx.new_bool(self.cif.call::<u8>(self.pointer, &ffi_args) != 0).into(),
-                        "P" => { // c_void_p
-                             let ptr_result = self.cif.call::<*mut std::ffi::c_void>(self.pointer, &ffi_args);
-                             if ptr_result.is_null() { vm.ctx.none() } else { vm.ctx.new_int(ptr_result as usize).into() }
+                    // Raw scalar pulled straight out of the libffi call, still under
+                    // `errno_guard`; `PyObjectRef` conversion happens only after it's
+                    // dropped below.
+                    enum RawResult {
+                        I32(i32), U32(u32), I64(i64), U64(u64),
+                        I8(i8), U8(u8), I16(i16), U16(u16),
+                        F32(f32), F64(f64), Bool(bool),
+                        Ptr(*mut std::ffi::c_void),
+                        CStr(*mut std::ffi::c_char),
+                    }
+
+                    let errno_guard = ErrnoSwapGuard::enter(use_errno, use_last_error);
+                    let raw = match type_char {
+                        "i" | "l" => RawResult::I32(cif.call(self.pointer, &ffi_args)),
+                        "I" | "L" => RawResult::U32(cif.call(self.pointer, &ffi_args)),
+                        "q" => RawResult::I64(cif.call(self.pointer, &ffi_args)),
+                        "Q" => RawResult::U64(cif.call(self.pointer, &ffi_args)),
+                        "b" => RawResult::I8(cif.call(self.pointer, &ffi_args)),
+                        "B" => RawResult::U8(cif.call(self.pointer, &ffi_args)),
+                        "h" => RawResult::I16(cif.call(self.pointer, &ffi_args)),
+                        "H" => RawResult::U16(cif.call(self.pointer, &ffi_args)),
+                        "f" => RawResult::F32(cif.call(self.pointer, &ffi_args)),
+                        "d" => RawResult::F64(cif.call(self.pointer, &ffi_args)),
+                        "?" => RawResult::Bool(cif.call::<u8>(self.pointer, &ffi_args) != 0),
+                        "P" => RawResult::Ptr(cif.call(self.pointer, &ffi_args)), // c_void_p
+                        "z" => RawResult::CStr(cif.call(self.pointer, &ffi_args)), // c_char_p
+                        // "Z" => // c_wchar_p - TODO: Requires knowing wchar_t size and proper conversion
+                        _ => return Err(vm.new_type_error(format!("Unsupported _type_ string '{}' in restype for result conversion", type_char))),
+                    };
+                    drop(errno_guard);
+
+                    match raw {
+                        RawResult::I32(v) => vm.ctx.new_int(v).into(),
+                        RawResult::U32(v) => vm.ctx.new_int(v).into(),
+                        RawResult::I64(v) => vm.ctx.new_int(v).into(),
+                        RawResult::U64(v) => vm.ctx.new_int(v).into(),
+                        RawResult::I8(v) => vm.ctx.new_int(v).into(),
+                        RawResult::U8(v) => vm.ctx.new_int(v).into(),
+                        RawResult::I16(v) => vm.ctx.new_int(v).into(),
+                        RawResult::U16(v) => vm.ctx.new_int(v).into(),
+                        RawResult::F32(v) => vm.ctx.new_float(v as f64).into(),
+                        RawResult::F64(v) => vm.ctx.new_float(v).into(),
+                        RawResult::Bool(v) => vm.ctx.new_bool(v).into(),
+                        RawResult::Ptr(ptr_result) => {
+                            if ptr_result.is_null() { vm.ctx.none() } else { vm.ctx.new_int(ptr_result as usize).into() }
                         }
-                        "z" => { // c_char_p
-                            let ptr_result = self.cif.call::<*mut std::ffi::c_char>(self.pointer, &ffi_args);
+                        RawResult::CStr(ptr_result) => {
                             if ptr_result.is_null() {
                                 vm.ctx.none()
                             } else {
@@ -203,16 +939,16 @@ impl Function {
                                 vm.ctx.new_bytes(c_str.to_bytes().to_vec()).into()
                             }
                         }
-                        // "Z" => { // c_wchar_p - TODO: Requires knowing wchar_t size and proper conversion
-                        //    return Err(vm.new_not_implemented_error("c_wchar_p restype not implemented".to_string()));
-                        // }
-                        _ => return Err(vm.new_type_error(format!("Unsupported _type_ string '{}' in restype for result conversion", type_char))),
                     }
                 } else if original_restype_obj.is_callable(vm) {
                     // Assumed FFI return is c_int for this case.
-                    let raw_int_result = self.cif.call::<i32>(self.pointer, &ffi_args);
+                    let errno_guard = ErrnoSwapGuard::enter(use_errno, use_last_error);
+                    let raw_int_result = cif.call::<i32>(self.pointer, &ffi_args);
+                    // Drop the errno guard now: the libffi call is done, and the callable
+                    // below is arbitrary Python code that must not see the swapped errno.
+                    drop(errno_guard);
                     let py_int_result = vm.ctx.new_int(raw_int_result).into();
-                    original_restype_obj.call((py_int_result,), vm)?
+                    return original_restype_obj.call((py_int_result,), vm);
                 } else {
                      return Err(vm.new_type_error(format!("Invalid original_restype ({:?}) found during call", original_restype_obj.class().name())));
                 }
@@ -233,9 +969,23 @@ pub struct PyCFuncPtr {
     pub handler: PyObjectRef, 
     pub abi: PyRwLock<Abi>,
     // Add a field to store library name, used by functions from CDLL
-    pub library_name: Option<String>,
+    pub library_name: Option<super::loaders::LibRef>,
+    // The dlopen/LoadLibrary flags the owning library was loaded with; part of the
+    // LIBCACHE key since a path loaded RTLD_LOCAL and RTLD_GLOBAL are distinct handles.
+    pub library_mode: i32,
+    // Whether this function's calls should swap the private ctypes errno / GetLastError
+    // around the libffi call, matching CPython's CDLL(..., use_errno=True) semantics.
+    pub use_errno: bool,
+    pub use_last_error: bool,
     pub _argtypes_: PyRwLock<Option<PyObjectRef>>,
     pub _errcheck_: PyRwLock<Option<PyObjectRef>>, // Added _errcheck_ field
+    // Cached `Function` (symbol lookup + base `Cif` + `variadic_cifs` cache) for
+    // CDLL-backed functions, along with the `_restype_`/`_argtypes_`/`abi` it was
+    // built from. `Function::load` does real work (symbol resolution, building a
+    // `Cif`) on every call, so without this the `variadic_cifs` cache it carries
+    // would never survive past the call that populated it. Rebuilt only when
+    // `_restype_`/`_argtypes_`/`abi` have since been reassigned.
+    function_cache: PyRwLock<Option<(Option<PyObjectRef>, Option<PyObjectRef>, Abi, Function)>>,
 }
 
 impl Debug for PyCFuncPtr {
@@ -253,21 +1003,46 @@ impl PyCFuncPtr {
     // Constructor for functions obtained from CDLL
     pub(crate) fn new_for_dll(
         name: PyStrRef,
-        library_name: String,
+        library_name: super::loaders::LibRef,
+        library_mode: i32,
         abi: Abi,
         vm: &VirtualMachine,
     ) -> PyResult {
+        Self::new_for_dll_with_errno(name, library_name, library_mode, abi, false, false, vm)
+    }
+
+    pub(crate) fn new_for_dll_with_errno(
+        name: PyStrRef,
+        library_name: super::loaders::LibRef,
+        library_mode: i32,
+        abi: Abi,
+        use_errno: bool,
+        use_last_error: bool,
+        vm: &VirtualMachine,
+    ) -> PyResult {
+        let mut flags = FUNCFLAG_CDECL;
+        if use_errno {
+            flags |= FUNCFLAG_USE_ERRNO;
+        }
+        if use_last_error {
+            flags |= FUNCFLAG_USE_LASTERROR;
+        }
+
         Ok(PyCFuncPtr {
             name: PyRwLock::new(name.as_str().to_owned()),
-            _flags_: AtomicCell::new(0), // Default flags
+            _flags_: AtomicCell::new(flags),
             _restype_: PyRwLock::new(None), // Default restype
             // For DLL functions, handler might not be a Python callable in the same way.
             // Store library_name and use a placeholder or vm.ctx.none() for handler if not applicable.
-            handler: vm.ctx.none(), 
+            handler: vm.ctx.none(),
             abi: PyRwLock::new(abi),
             library_name: Some(library_name),
+            library_mode,
+            use_errno,
+            use_last_error,
             _argtypes_: PyRwLock::new(None),
             _errcheck_: PyRwLock::new(None), // Initialize _errcheck_
+            function_cache: PyRwLock::new(None),
         }
         .into_pyobject(vm))
     }
@@ -287,26 +1062,53 @@ impl Constructor for PyCFuncPtr {
         let name_obj = &elements[0];
         let handler = elements[1].clone(); // This is the callable for CFUNCTYPE
 
-        // TODO: Parse other arguments like restype, flags if provided for CFUNCTYPE
+        // TODO: Parse other arguments like restype if provided for CFUNCTYPE
         // For now, simplified:
         let name = name_obj.downcast_ref::<PyStr>()
             .ok_or_else(|| vm.new_type_error("First argument must be a string (function name)".to_string()))?
             .as_str()
             .to_owned();
-        
-        // Default ABI for CFUNCTYPE, usually CDECL unless specified otherwise
-        let default_abi = Abi::Cdecl; 
-        // Potentially parse flags from args_after_tuple to change ABI if needed for CFUNCTYPE
+
+        // Default ABI for CFUNCTYPE: always the platform's default C calling convention
+        // here (`_abi_` is read-only; WinDLL-style `Stdcall` functions are built by
+        // `new_for_dll` instead).
+        let default_abi = default_c_abi();
+
+        // Flags may be given as an explicit `flags` kwarg (a raw FUNCFLAG_* bitmask)
+        // and/or as `use_errno`/`use_last_error` kwargs, which are OR'd into it. Either
+        // way they all boil down to the same `_flags_` bits that `Function::call`'s
+        // `ErrnoSwapGuard` consults.
+        let mut flags = FUNCFLAG_CDECL;
+        if let Some(flags_obj) = args_after_tuple.get_optional_kwarg("flags") {
+            if let Some(int_obj) = flags_obj.downcast_ref::<crate::builtins::PyInt>() {
+                flags = int_obj.as_bigint().to_u32().unwrap_or(FUNCFLAG_CDECL);
+            }
+        }
+        if let Some(use_errno_obj) = args_after_tuple.get_optional_kwarg("use_errno") {
+            if vm.is_true(&use_errno_obj)? {
+                flags |= FUNCFLAG_USE_ERRNO;
+            }
+        }
+        if let Some(use_last_error_obj) = args_after_tuple.get_optional_kwarg("use_last_error") {
+            if vm.is_true(&use_last_error_obj)? {
+                flags |= FUNCFLAG_USE_LASTERROR;
+            }
+        }
+        let (use_errno, use_last_error) = errno_flags(flags);
 
         Ok(Self {
             name: PyRwLock::new(name),
-            _flags_: AtomicCell::new(0), // Initialize flags, could be parsed from args
+            _flags_: AtomicCell::new(flags),
             _restype_: PyRwLock::new(None), // Initialize restype, could be parsed from args
             handler, // For CFUNCTYPE, this is the Python callable
             abi: PyRwLock::new(default_abi),
             library_name: None, // Not from a DLL
+            library_mode: 0,
+            use_errno,
+            use_last_error,
             _argtypes_: PyRwLock::new(None),
             _errcheck_: PyRwLock::new(None), // Initialize _errcheck_
+            function_cache: PyRwLock::new(None),
         }
         .to_pyobject(vm))
     }
@@ -321,34 +1123,49 @@ impl Callable for PyCFuncPtr {
             if zelf.library_name.is_some() {
                 // This is a function from a CDLL object
                 let lib_name = zelf.library_name.as_ref().unwrap(); // Safe due to check
-                
-                // Access the global LIBCACHE from loaders.rs (need to make it accessible, or pass vm.stdlib_ctypes_libcache)
-                // For now, assuming direct access or a helper function to get LIBCACHE.
-                // This part needs careful handling of LIBCACHE visibility.
-                // Let's assume vm has a way to get to LIBCACHE for now.
-                // This is a simplified placeholder for library loading:
-                let library_cache_static = &super::loaders::LIBCACHE; // This is a placeholder, proper access TBD
+                let lib_key: super::loaders::LibKey = (lib_name.clone(), zelf.library_mode);
+
+                let library_cache_static = &super::loaders::LIBCACHE;
                 let lib_cache_read_guard = library_cache_static.read();
-                let library = lib_cache_read_guard.get(lib_name)
-                    .ok_or_else(|| vm.new_os_error(format!("Library {} not found or unloaded", lib_name)))?;
+                let library = &lib_cache_read_guard.get(&lib_key)
+                    .ok_or_else(|| vm.new_os_error(format!("Library {} not found or unloaded", lib_name)))?
+                    .library;
 
                 // Now use `library` (which is a libloading::Library)
                 let name = zelf.name.read();
-                let restype_obj = zelf._restype_.read().clone(); 
+                let restype_obj = zelf._restype_.read().clone();
                 let argtypes_opt = zelf._argtypes_.read().clone();
                 let abi_val = zelf.abi.read().clone(); // Read ABI value
 
-                let func = Function::load(
-                    library, 
-                    &name,
-                    argtypes_opt, 
-                    restype_obj,  
-                    abi_val, // Pass ABI value to Function::load
-                    vm,
-                )?;
+                // Reuse the cached `Function` (and the `variadic_cifs` Cif cache
+                // it carries) across calls as long as `_restype_`/`_argtypes_`/`abi`
+                // haven't been reassigned since it was built, instead of paying for
+                // symbol lookup + `Cif` construction on every single call.
+                let cache_is_fresh = zelf.function_cache.read().as_ref().map_or(
+                    false,
+                    |(cached_restype, cached_argtypes, cached_abi, _)| {
+                        option_pyobj_is(cached_restype, &restype_obj)
+                            && option_pyobj_is(cached_argtypes, &argtypes_opt)
+                            && *cached_abi == abi_val
+                    },
+                );
+                if !cache_is_fresh {
+                    let func = Function::load(
+                        library,
+                        &name,
+                        argtypes_opt.clone(),
+                        restype_obj.clone(),
+                        abi_val.clone(), // Pass ABI value to Function::load
+                        vm,
+                    )?;
+                    *zelf.function_cache.write() = Some((restype_obj, argtypes_opt, abi_val, func));
+                }
+                let cache_guard = zelf.function_cache.read();
+                let func = &cache_guard.as_ref().unwrap().3;
                 // func.args (Type vector) should now be derived from _argtypes_ if it was provided.
                 // func.call will use this to validate/convert runtime Python args.
-                let raw_py_result = func.call(args.args.clone(), vm)?; // args.args are the runtime Python arguments
+                let raw_py_result = func.call(args.args.clone(), zelf.use_errno, zelf.use_last_error, vm)?; // args.args are the runtime Python arguments
+                drop(cache_guard);
 
                 if let Some(errcheck_callable) = zelf._errcheck_.read().clone() {
                     let py_args_tuple = vm.ctx.new_tuple(args.args);
@@ -357,18 +1174,32 @@ impl Callable for PyCFuncPtr {
                     //   result: the result from the C function call
                     //   func: the CFuncPtr object itself
                     //   arguments: the original tuple of arguments passed to the function call
-                    vm.invoke(&errcheck_callable, (raw_py_result, zelf.as_object().clone(), py_args_tuple))
+                    let errcheck_result = vm.invoke(
+                        &errcheck_callable,
+                        (raw_py_result.clone(), zelf.as_object().clone(), py_args_tuple.clone()),
+                    )?;
+                    // CPython sentinel: if errcheck hands back the `arguments`
+                    // tuple unchanged, that means "nothing to report, use the
+                    // original result" rather than "replace the result with
+                    // the arguments tuple".
+                    if errcheck_result.is(&py_args_tuple) {
+                        Ok(raw_py_result)
+                    } else {
+                        Ok(errcheck_result)
+                    }
                 } else {
                     Ok(raw_py_result)
                 }
             } else {
-                // This is a CFUNCTYPE (handler is a Python callable)
-                // The existing logic for CFUNCTYPE would go here, calling `zelf.handler`
-                // This part is complex and involves creating a Cif and calling the Python handler.
-                // For now, returning NotImplementedError for CFUNCTYPE calls.
-                Err(vm.new_not_implemented_error(
-                    "Calling CFUNCTYPE instances not fully implemented here yet".to_string()
-                ))
+                // This is a CFUNCTYPE (handler is a Python callable). When the
+                // *Python* side calls it directly (as opposed to a C caller
+                // going through the function pointer handed out by
+                // `PyCallbackObject::address`), CPython's ctypes just invokes
+                // the wrapped callable directly rather than round-tripping
+                // through libffi - the closure/trampoline machinery in
+                // `PyCallbackObject` only matters once the raw address
+                // escapes to C.
+                vm.invoke(&zelf.handler, args.args.clone())
             }
         }
     }
@@ -396,7 +1227,205 @@ pub struct PyCallbackSignature {
     pub ffi_argtypes: Vec<libffi::middle::Type>,
     pub ffi_restype: libffi::middle::Type,
     pub abi: libffi::middle::Abi,
-    // TODO: flags like use_errno, use_last_error
+    // Mirrors `PyCFuncPtr::use_errno`/`use_last_error`: whether a C library invoking this
+    // callback through its trampoline should see ctypes' private errno / GetLastError
+    // swapped in for the duration of the Python callable, the same way `Function::call`
+    // brackets outbound calls with `ErrnoSwapGuard`.
+    pub use_errno: bool,
+    pub use_last_error: bool,
+}
+
+// Everything a libffi closure trampoline needs once it's been handed off to
+// C code: the Python callable to run, the signature describing how to read
+// the raw argument slots and write the raw result slot, and a place to stash
+// an exception that can't be propagated across the C boundary.
+struct ClosureUserData {
+    callable: PyObjectRef,
+    signature: PyCallbackSignature,
+    // SAFETY: ctypes callbacks are only ever meant to be invoked by C code
+    // while the interpreter that created them is still alive, the same way
+    // CPython's callbacks assume the GIL-holding interpreter is live.
+    // RustPython doesn't have a global "current VM" handle to look up at
+    // call time, so we capture the one in scope when the closure was built;
+    // this dangles if the callback fires after that `VirtualMachine` drops.
+    vm: *const VirtualMachine,
+    // Populated by the trampoline if marshalling the arguments or the Python
+    // callable raised, instead of the exception unwinding across the FFI call.
+    // Nothing reads it back: `PyCallbackObject` keeps no reference to this
+    // userdata, so the stored exception is never re-raised.
+    pending_error: PyRwLock<Option<crate::builtins::PyBaseExceptionRef>>,
+}
+
+// The trampoline only ever touches `ClosureUserData` through the `&'static`
+// reference libffi hands it back; nothing here is actually safe to share
+// across threads without the owning interpreter's cooperation, but we need
+// `Send + Sync` to store a `Closure` inside a `PyPayload`.
+unsafe impl Send for ClosureUserData {}
+unsafe impl Sync for ClosureUserData {}
+
+unsafe fn raw_slot_to_pyobject(ctype_obj: &PyObjectRef, slot: *const c_void, vm: &VirtualMachine) -> PyObjectRef {
+    let type_char = ctype_obj
+        .get_attr("_type_", vm)
+        .ok()
+        .and_then(|t| t.downcast_ref::<PyStr>().map(|s| s.as_str().to_owned()));
+
+    match type_char.as_deref() {
+        Some("i") | Some("l") => vm.ctx.new_int(*(slot as *const i32)).into(),
+        Some("I") | Some("L") => vm.ctx.new_int(*(slot as *const u32)).into(),
+        Some("q") => vm.ctx.new_int(*(slot as *const i64)).into(),
+        Some("Q") => vm.ctx.new_int(*(slot as *const u64)).into(),
+        Some("b") => vm.ctx.new_int(*(slot as *const i8)).into(),
+        Some("B") => vm.ctx.new_int(*(slot as *const u8)).into(),
+        Some("h") => vm.ctx.new_int(*(slot as *const i16)).into(),
+        Some("H") => vm.ctx.new_int(*(slot as *const u16)).into(),
+        Some("f") => vm.ctx.new_float(*(slot as *const f32) as f64).into(),
+        Some("d") => vm.ctx.new_float(*(slot as *const f64)).into(),
+        Some("?") => vm.ctx.new_bool(*(slot as *const u8) != 0).into(),
+        Some("P") => {
+            let raw = *(slot as *const usize);
+            if raw == 0 { vm.ctx.none() } else { vm.ctx.new_int(raw).into() }
+        }
+        Some("z") => {
+            let raw = *(slot as *const *const std::os::raw::c_char);
+            if raw.is_null() {
+                vm.ctx.none()
+            } else {
+                let c_str = std::ffi::CStr::from_ptr(raw);
+                vm.ctx.new_bytes(c_str.to_bytes().to_vec()).into()
+            }
+        }
+        // Structure/Union and array fields have no scalar `_type_` of their own -
+        // `read_scalar_from_buffer` recurses through `_fields_`/`_length_` before ever
+        // reaching here, so this function only needs to handle genuinely scalar
+        // types. TODO: c_wchar_p arguments still aren't marshalled.
+        _ => vm.ctx.none(),
+    }
+}
+
+unsafe fn write_pyobject_to_result_slot(
+    restype_obj: &PyObjectRef,
+    py_result: &PyObjectRef,
+    result: &mut c_void,
+    vm: &VirtualMachine,
+) {
+    let dst = result as *mut c_void;
+    let type_char = restype_obj
+        .get_attr("_type_", vm)
+        .ok()
+        .and_then(|t| t.downcast_ref::<PyStr>().map(|s| s.as_str().to_owned()));
+
+    let as_i64 = py_result
+        .downcast_ref::<crate::builtins::PyInt>()
+        .and_then(|i| i.as_bigint().to_i64())
+        .unwrap_or(0);
+    let as_f64 = py_result
+        .downcast_ref::<crate::builtins::PyFloat>()
+        .map(|f| f.to_f64())
+        .unwrap_or(0.0);
+    let as_bool = as_i64 != 0;
+
+    match type_char.as_deref() {
+        Some("i") | Some("l") => ptr::write(dst as *mut i32, as_i64 as i32),
+        Some("I") | Some("L") => ptr::write(dst as *mut u32, as_i64 as u32),
+        Some("q") => ptr::write(dst as *mut i64, as_i64),
+        Some("Q") => ptr::write(dst as *mut u64, as_i64 as u64),
+        Some("b") => ptr::write(dst as *mut i8, as_i64 as i8),
+        Some("B") => ptr::write(dst as *mut u8, as_i64 as u8),
+        Some("h") => ptr::write(dst as *mut i16, as_i64 as i16),
+        Some("H") => ptr::write(dst as *mut u16, as_i64 as u16),
+        Some("f") => ptr::write(dst as *mut f32, as_f64 as f32),
+        Some("d") => ptr::write(dst as *mut f64, as_f64),
+        Some("?") => ptr::write(dst as *mut u8, as_bool as u8),
+        Some("P") => ptr::write(dst as *mut usize, as_i64 as usize),
+        // No restype (None) means the C side ignores whatever we write - zero it
+        // defensively - and an aggregate/unrecognized field (no scalar `_type_` of its
+        // own) falls back the same way. Either way, bound the write to the type's
+        // actual size via `size_align_of` rather than always zeroing a full `usize`:
+        // this slot may be a narrow field near the end of a larger buffer (a struct
+        // field, or a result written through `write_scalar_into_buffer`), and an
+        // unconditional 8-byte zero would write past it. Only genuinely unknown
+        // restypes (no `_fields_`/`_length_`/`_type_` at all, i.e. `None`) fall back to
+        // `usize`'s width.
+        _ => {
+            let size = size_align_of(restype_obj, vm)
+                .map(|(size, _align)| size)
+                .unwrap_or_else(|_| mem::size_of::<usize>());
+            ptr::write_bytes(dst as *mut u8, 0, size);
+        }
+    }
+}
+
+// A Python exception can't unwind across the C boundary. CPython's callback
+// trampoline reports this the same way it reports an error from any other context
+// with no Python frame to propagate to - `PyErr_WriteUnraisable` - so do the same here
+// instead of letting it vanish silently. Also store it in the userdata's
+// `pending_error` (which nothing currently reads back), and hand the C caller a zeroed
+// value rather than letting it read garbage.
+unsafe fn report_callback_error(
+    vm: &VirtualMachine,
+    userdata: &ClosureUserData,
+    result: &mut c_void,
+    context: &str,
+    exc: crate::builtins::PyBaseExceptionRef,
+) {
+    vm.run_unraisable(exc.clone(), Some(context.to_owned()), userdata.callable.clone());
+    *userdata.pending_error.write() = Some(exc);
+    ptr::write_bytes(result as *mut c_void as *mut u8, 0, mem::size_of::<usize>());
+}
+
+unsafe extern "C" fn closure_trampoline(
+    _cif: &libffi::low::ffi_cif,
+    result: &mut c_void,
+    args: *const *const c_void,
+    userdata: &ClosureUserData,
+) {
+    let vm = &*userdata.vm;
+
+    let argtypes: Vec<PyObjectRef> = userdata
+        .signature
+        .python_argtypes
+        .downcast_ref::<PyTuple>()
+        .map(|t| t.as_slice().to_vec())
+        .unwrap_or_default();
+
+    let py_args: Vec<PyObjectRef> = match argtypes
+        .iter()
+        .enumerate()
+        .map(|(i, argtype)| read_scalar_from_buffer(argtype, *args.add(i) as *const u8, vm))
+        .collect::<PyResult<Vec<PyObjectRef>>>()
+    {
+        Ok(py_args) => py_args,
+        Err(exc) => {
+            report_callback_error(
+                vm,
+                userdata,
+                result,
+                "exception ignored while marshalling ctypes callback arguments",
+                exc,
+            );
+            return;
+        }
+    };
+
+    let errno_guard = ErrnoSwapGuard::enter(
+        userdata.signature.use_errno,
+        userdata.signature.use_last_error,
+    );
+    let invoke_result = vm.invoke(&userdata.callable, py_args);
+    drop(errno_guard);
+
+    match invoke_result {
+        Ok(py_result) => {
+            write_pyobject_to_result_slot(&userdata.signature.python_restype, &py_result, result, vm);
+        }
+        Err(exc) => report_callback_error(
+            vm,
+            userdata,
+            result,
+            "exception ignored from ctypes callback",
+            exc,
+        ),
+    }
 }
 
 #[pyclass(name = "PyCallback", module = "_ctypes", base = "PyCData", with(Constructor))]
@@ -404,8 +1433,48 @@ pub struct PyCallbackSignature {
 pub struct PyCallbackObject {
     pub callable: PyObjectRef, // The user's Python function
     pub signature: Option<PyCallbackSignature>, // The signature it was created with
-    // TODO: closure: Option<libffi::middle::Closure<'static>>,
-    // TODO: address: usize,
+    // The closure handed out to C code, and the raw address of its trampoline.
+    // `Closure` borrows its userdata, so we leak a `Box<ClosureUserData>` to
+    // get a `'static` reference - the userdata then lives for as long as the
+    // process, while the closure itself is freed when this object is dropped.
+    closure: PyRwLock<Option<Closure<'static>>>,
+    address: AtomicCell<usize>,
+}
+
+// `Closure` isn't `Send`/`Sync` on its own (it wraps a raw code pointer and
+// borrowed userdata), but nothing in it is actually mutated after
+// construction, so sharing it across threads the way `PyPayload` requires
+// is sound.
+unsafe impl Send for PyCallbackObject {}
+unsafe impl Sync for PyCallbackObject {}
+
+impl PyCallbackObject {
+    // Builds the libffi closure and exposes its trampoline as a raw address,
+    // once a `PyCallbackSignature` is available for this callable. Kept
+    // separate from `py_new`, which has no signature to work with; the
+    // `CFUNCTYPE` factory below calls this directly with the signature it
+    // built.
+    pub(crate) fn build_closure(callable: PyObjectRef, signature: PyCallbackSignature, vm: &VirtualMachine) -> Self {
+        let mut cif = Cif::new(signature.ffi_argtypes.clone(), signature.ffi_restype.clone());
+        cif.set_abi(signature.abi);
+        let userdata = Box::leak(Box::new(ClosureUserData {
+            callable: callable.clone(),
+            signature: signature.clone(),
+            vm: vm as *const VirtualMachine,
+            pending_error: PyRwLock::new(None),
+        }));
+        let closure = Closure::new(cif, closure_trampoline, userdata);
+        // `CodePtr` is a thin tuple-struct wrapper around the raw function
+        // pointer, the same shape `Function::load` constructs above.
+        let address = closure.code_ptr().0 as usize;
+
+        PyCallbackObject {
+            callable,
+            signature: Some(signature),
+            closure: PyRwLock::new(Some(closure)),
+            address: AtomicCell::new(address),
+        }
+    }
 }
 
 impl Constructor for PyCallbackObject {
@@ -416,20 +1485,93 @@ impl Constructor for PyCallbackObject {
         if !callable_arg.is_callable(vm) {
             return Err(vm.new_type_error("Argument must be a callable".to_string()));
         }
-        // TODO: In a later step (6.D), this is where we'd retrieve the PyCallbackSignature
-        // from `cls.payload()` or similar, once CFUNCTYPE sets it up.
-        // For now, initialize with a placeholder or None for signature.
+        // Plain `PyCallback(some_callable)` construction has no signature to
+        // build a `Cif` from, so the instance is only usable for direct
+        // Python-side calls, not as a raw C function pointer - callers that
+        // need an actual address should go through the `CFUNCTYPE` module
+        // function below, which already has a signature in hand and calls
+        // `build_closure` directly rather than routing through here.
         let instance = PyCallbackObject {
             callable: callable_arg,
-            signature: None, // Placeholder
-            // closure: None, // Placeholder
-            // address: 0,    // Placeholder
+            signature: None,
+            closure: PyRwLock::new(None),
+            address: AtomicCell::new(0),
         };
         instance.into_pyobject_with_type(vm, cls)
     }
 }
 impl DefaultPyObject for PyCallbackObject {} // Needed if no custom constructor for PyCallbackObject itself if not for `with(Constructor)`
 
+#[pyclass]
+impl PyCallbackObject {
+    // The trampoline's entry point, suitable for handing to C as a raw
+    // function pointer (e.g. via a `c_void_p`). Zero until a signature has
+    // been attached and `build_closure` has actually run.
+    #[pygetset(name = "address")]
+    fn address(&self) -> usize {
+        self.address.load()
+    }
+}
+
+// #################################################################
+// ## CFUNCTYPE(restype, *argtypes) factory
+// #################################################################
+//
+// `CFUNCTYPE(restype, *argtypes, use_errno=False, use_last_error=False)`
+// builds the `PyCallbackSignature` a closure needs and hands back something
+// callable with a Python function - the moment that's called,
+// `PyCallbackObject::build_closure` runs and produces the real
+// `ffi_closure`/trampoline/address. CPython instead mints a distinct
+// `_CFuncPtr` subclass per signature and defers the closure build to
+// `__init__`; we get the same observable behavior (call the factory, get
+// back an address-bearing callback object) without needing a way to attach
+// extra state to a freshly-created Python type, which this tree has no
+// machinery for.
+fn cfunctype(proto: FuncArgs, vm: &VirtualMachine) -> PyResult<PyObjectRef> {
+    let mut positional = proto.args.into_iter();
+    let restype_obj = positional.next().unwrap_or_else(|| vm.ctx.none());
+    let argtype_objs: Vec<PyObjectRef> = positional.collect();
+
+    let ffi_restype = if vm.is_none(&restype_obj) {
+        Type::void()
+    } else {
+        resolve_ffi_type(&restype_obj, vm)?
+    };
+    let ffi_argtypes = argtype_objs
+        .iter()
+        .map(|t| resolve_ffi_type(t, vm))
+        .collect::<PyResult<Vec<Type>>>()?;
+    let python_argtypes = vm.ctx.new_tuple(argtype_objs);
+
+    let use_errno = proto
+        .get_optional_kwarg("use_errno")
+        .map(|v| vm.is_true(&v))
+        .transpose()?
+        .unwrap_or(false);
+    let use_last_error = proto
+        .get_optional_kwarg("use_last_error")
+        .map(|v| vm.is_true(&v))
+        .transpose()?
+        .unwrap_or(false);
+
+    let signature = PyCallbackSignature {
+        python_restype: restype_obj,
+        python_argtypes,
+        ffi_argtypes,
+        ffi_restype,
+        abi: default_c_abi(),
+        use_errno,
+        use_last_error,
+    };
+
+    Ok(vm.ctx.new_function("CFUNCTYPE_instance", move |callable: PyObjectRef, vm: &VirtualMachine| -> PyResult {
+        if !callable.is_callable(vm) {
+            return Err(vm.new_type_error("CFUNCTYPE instance must be called with a callable".to_string()));
+        }
+        PyCallbackObject::build_closure(callable, signature.clone(), vm).into_pyobject(vm)
+    }))
+}
+
 // Add init_types if it's not already there, or modify existing one
 // Assuming init_types from previous tasks exists and needs modification.
 // If it doesn't exist, it should be created like:
@@ -481,13 +1623,27 @@ impl PyCFuncPtr {
     }
 
     #[pygetset(name = "_restype_", setter)]
-    fn set_restype(&self, restype: PyObjectRef, vm: &VirtualMachine) { // Changed parameter type & signature
-        // CPython allows setting restype to None, a ctypes type, or a callable.
+    fn set_restype(&self, restype: PyObjectRef, vm: &VirtualMachine) -> PyResult<()> {
+        // CPython allows setting restype to None, a ctypes type (the raw
+        // return buffer is converted to an instance of it), or an arbitrary
+        // callable (invoked on the raw result, its return value used as-is -
+        // e.g. `func.restype = lambda code: MyError(code)`).
         if vm.is_none(&restype) {
             *self._restype_.write() = None;
-        } else {
-            *self._restype_.write() = Some(restype);
+            return Ok(());
         }
+        let is_ctypes_type = restype
+            .clone()
+            .downcast::<PyTypeRef>()
+            .map(|t| t.is_subclass(PyCData::class(&vm.ctx).as_ref(), vm))
+            .unwrap_or(false);
+        if !is_ctypes_type && !restype.is_callable(vm) {
+            return Err(vm.new_type_error(
+                "restype must be a ctypes type, a callable, or None".to_string(),
+            ));
+        }
+        *self._restype_.write() = Some(restype);
+        Ok(())
     }
 
     // Add methods to get/set ABI if needed, or handle through flags
@@ -496,11 +1652,14 @@ impl PyCFuncPtr {
         match *self.abi.read() {
             Abi::Cdecl => Ok("cdecl".to_string()),
             Abi::Stdcall => Ok("stdcall".to_string()),
-            Abi::Default => Ok("default".to_string()), 
-            // Consider adding more specific ABI names if they become relevant
-            // for ctypes usage in RustPython (e.g., Fastcall, SystemV).
-            // For now, "unknown" covers other specific but less common ABIs.
-            _ => Ok("unknown".to_string()), 
+            Abi::Fastcall => Ok("fastcall".to_string()),
+            Abi::Thiscall => Ok("thiscall".to_string()),
+            Abi::Win64 => Ok("win64".to_string()),
+            Abi::Sysv => Ok("sysv".to_string()),
+            Abi::Default => Ok("default".to_string()),
+            // Other `ffi_abi` values libffi may define on exotic targets (e.g. ARM's
+            // EABI/VFP variants) don't have a CPython-recognized name to report.
+            _ => Ok("unknown".to_string()),
         }
     }
 
@@ -532,10 +1691,27 @@ impl PyCFuncPtr {
             return Err(vm.new_type_error("argtypes must be a tuple, list, or None".to_string()));
         };
 
-        // Optional: Validate elements of the tuple are valid ctypes type objects.
-        // This could involve checking if each element is a PyTypeRef that is a subclass of PyCData,
-        // or has a _type_ attribute, etc.
-        // For now, just storing the tuple is acceptable as per subtask.
+        // Each element must be a genuine ctypes type (a `PyCData` subclass)
+        // or at least expose a `from_param` classmethod of its own, the way
+        // a user-defined type participating in the `from_param` protocol
+        // would - anything else can never produce a value `Function::call`
+        // knows how to marshal.
+        let elements = tuple_val.downcast_ref::<PyTuple>().unwrap().as_slice().to_vec();
+        for (i, argtype_obj) in elements.iter().enumerate() {
+            let is_ctypes_type = argtype_obj
+                .clone()
+                .downcast::<PyTypeRef>()
+                .map(|t| t.is_subclass(PyCData::class(&vm.ctx).as_ref(), vm))
+                .unwrap_or(false);
+            let has_from_param = argtype_obj.get_attr("from_param", vm).is_ok();
+            if !is_ctypes_type && !has_from_param {
+                return Err(vm.new_type_error(format!(
+                    "item {} in _argtypes_ is not a ctypes type or a type exposing from_param",
+                    i
+                )));
+            }
+        }
+
         *self._argtypes_.write() = Some(tuple_val);
         Ok(())
     }
@@ -565,38 +1741,456 @@ impl PyCFuncPtr {
 }
 
 
-pub(super) fn init_types(vm: &VirtualMachine, module: &PyObjectRef) { // Modified
-    PyCFuncPtr::extend_class(&vm.ctx, PyCFuncPtr::static_type().as_ref());
-    PyCFuncTypeType::extend_class(&vm.ctx, PyCFuncTypeType::static_type().as_ref());
-    PyCallbackObject::extend_class(&vm.ctx, PyCallbackObject::static_type().as_ref());
+// #################################################################
+// ## cdef-style C prototype parser (populates _argtypes_/_restype_)
+// #################################################################
+//
+// Mirrors cffi's `cdef` workflow: parse a C function declaration string
+// (e.g. `"int strcmp(const char *, const char *)"`) instead of requiring
+// the caller to hand-build `_argtypes_`/`_restype_` one `_type_` char at a
+// time. The parser itself only builds a small intermediate type graph -
+// opcode-like nodes, closely following the compact representation cffi
+// builds internally - then a separate resolution pass turns that graph
+// into actual ctypes type objects by name-lookup against the `ctypes`
+// module, the same way the rest of this file treats ctypes types as opaque
+// `PyObjectRef`s carrying a `_type_` attribute rather than native Rust
+// types.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CPrimitive {
+    Void,
+    Bool,
+    Char,
+    SChar,
+    UChar,
+    Short,
+    UShort,
+    Int,
+    UInt,
+    Long,
+    ULong,
+    LongLong,
+    ULongLong,
+    Float,
+    Double,
 }
-                &res_type,
-                vm,
-            )?;
-            func.call(args.args, vm)
+
+impl CPrimitive {
+    /// The `ctypes` module attribute name backing this primitive.
+    fn ctypes_name(self) -> &'static str {
+        match self {
+            CPrimitive::Void => unreachable!("void is resolved to None, not a ctypes attribute"),
+            CPrimitive::Bool => "c_bool",
+            CPrimitive::Char => "c_char",
+            CPrimitive::SChar => "c_byte",
+            CPrimitive::UChar => "c_ubyte",
+            CPrimitive::Short => "c_short",
+            CPrimitive::UShort => "c_ushort",
+            CPrimitive::Int => "c_int",
+            CPrimitive::UInt => "c_uint",
+            CPrimitive::Long => "c_long",
+            CPrimitive::ULong => "c_ulong",
+            CPrimitive::LongLong => "c_longlong",
+            CPrimitive::ULongLong => "c_ulonglong",
+            CPrimitive::Float => "c_float",
+            CPrimitive::Double => "c_double",
         }
     }
 }
 
-#[pyclass(flags(BASETYPE), with(Callable, Constructor))]
-impl PyCFuncPtr {
-    #[pygetset(magic)]
-    fn name(&self) -> String {
-        self.name.read().clone()
+/// The intermediate type graph the parser produces: PRIMITIVE, POINTER,
+/// ARRAY and STRUCT_UNION nodes, closely following cffi's internal `cdef`
+/// representation. `Array` is part of the vocabulary for completeness
+/// (e.g. a struct field declared `int[4]`) even though a bare function
+/// prototype's own parameters always decay arrays to pointers per C's own
+/// parameter-passing rules, so `parse_param` never actually produces one.
+#[derive(Debug, Clone)]
+enum CTypeNode {
+    Primitive(CPrimitive),
+    Pointer(Box<CTypeNode>),
+    Array(Box<CTypeNode>, usize),
+    StructUnion(String),
+}
+
+/// The FUNCTION node: a prototype's return type, parameter types, and
+/// whether it ends in a variadic `...`.
+#[derive(Debug, Clone)]
+struct CFunctionNode {
+    restype: CTypeNode,
+    params: Vec<CTypeNode>,
+    variadic: bool,
+}
+
+/// Common typedefs a C prototype is likely to use that aren't literal
+/// `signed`/`unsigned`/width keywords, resolved to the fixed-width
+/// primitive ctypes already models rather than a platform-dependent alias.
+fn resolve_typedef(name: &str) -> Option<CPrimitive> {
+    Some(match name {
+        "size_t" | "uintptr_t" => CPrimitive::ULong,
+        "ssize_t" | "ptrdiff_t" | "intptr_t" => CPrimitive::Long,
+        "int8_t" => CPrimitive::SChar,
+        "uint8_t" => CPrimitive::UChar,
+        "int16_t" => CPrimitive::Short,
+        "uint16_t" => CPrimitive::UShort,
+        "int32_t" => CPrimitive::Int,
+        "uint32_t" => CPrimitive::UInt,
+        "int64_t" => CPrimitive::LongLong,
+        "uint64_t" => CPrimitive::ULongLong,
+        "wchar_t" => CPrimitive::UShort,
+        _ => return None,
+    })
+}
+
+/// Splits a declaration into a flat token stream: identifiers/keywords, the
+/// single-character punctuation this grammar needs (`(`, `)`, `,`, `*`,
+/// `[`, `]`), and a three-character `...` token for varargs.
+fn cdef_tokenize(src: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let chars: Vec<char> = src.chars().collect();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c == '.' && chars.get(i + 1) == Some(&'.') && chars.get(i + 2) == Some(&'.') {
+            tokens.push("...".to_string());
+            i += 3;
+        } else if c.is_alphanumeric() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            tokens.push(chars[start..i].iter().collect());
+        } else {
+            tokens.push(c.to_string());
+            i += 1;
+        }
     }
+    tokens
+}
 
-    #[pygetset(setter, magic)]
-    fn set_name(&self, name: String) {
-        *self.name.write() = name;
+/// A small recursive-descent parser over the token stream `cdef_tokenize`
+/// produces. Unsupported constructs raise a `ValueError` naming the
+/// offending token rather than panicking or guessing.
+struct CdefParser<'a> {
+    tokens: &'a [String],
+    pos: usize,
+}
+
+impl<'a> CdefParser<'a> {
+    fn peek(&self) -> Option<&str> {
+        self.tokens.get(self.pos).map(String::as_str)
     }
 
-    #[pygetset(name = "_restype_")]
-    fn restype(&self) -> Option<PyTypeRef> {
-        self._restype_.read().as_ref().cloned()
+    fn advance(&mut self) -> Option<&str> {
+        let tok = self.tokens.get(self.pos).map(String::as_str);
+        if tok.is_some() {
+            self.pos += 1;
+        }
+        tok
     }
 
-    #[pygetset(name = "_restype_", setter)]
-    fn set_restype(&self, restype: PyTypeRef) {
-        *self._restype_.write() = Some(restype);
+    fn expect(&mut self, expected: &str, vm: &VirtualMachine) -> PyResult<()> {
+        match self.advance() {
+            Some(tok) if tok == expected => Ok(()),
+            Some(tok) => Err(vm.new_value_error(format!("cdef: expected '{}', found '{}'", expected, tok))),
+            None => Err(vm.new_value_error(format!("cdef: expected '{}', found end of declaration", expected))),
+        }
+    }
+
+    /// Consumes `const`/`volatile` qualifiers (ignored) and the base-type
+    /// keywords (`signed`/`unsigned`/`char`/`short`/`int`/`long`/`float`/
+    /// `double`/`void`), a `struct`/`union` tag, or a single typedef name,
+    /// returning the resolved `CTypeNode` for everything up to (but not
+    /// including) any pointer `*`s.
+    fn parse_type_specifier(&mut self, vm: &VirtualMachine) -> PyResult<CTypeNode> {
+        let mut signed_seen = false;
+        let mut unsigned_seen = false;
+        let mut long_count = 0u32;
+        let mut base: Option<&'static str> = None;
+
+        loop {
+            match self.peek() {
+                Some("const") | Some("volatile") => {
+                    self.advance();
+                }
+                Some("signed") => {
+                    signed_seen = true;
+                    self.advance();
+                }
+                Some("unsigned") => {
+                    unsigned_seen = true;
+                    self.advance();
+                }
+                Some("long") => {
+                    long_count += 1;
+                    self.advance();
+                }
+                Some("void") => {
+                    base = Some("void");
+                    self.advance();
+                }
+                Some("char") => {
+                    base = Some("char");
+                    self.advance();
+                }
+                Some("short") => {
+                    base = Some("short");
+                    self.advance();
+                }
+                Some("int") => {
+                    base = Some("int");
+                    self.advance();
+                }
+                Some("float") => {
+                    base = Some("float");
+                    self.advance();
+                }
+                Some("double") => {
+                    base = Some("double");
+                    self.advance();
+                }
+                Some("struct") | Some("union") => {
+                    self.advance();
+                    let name = self
+                        .advance()
+                        .ok_or_else(|| vm.new_value_error("cdef: expected struct/union tag name".to_string()))?
+                        .to_string();
+                    return Ok(CTypeNode::StructUnion(name));
+                }
+                _ => break,
+            }
+        }
+
+        let primitive = if let Some(b) = base {
+            match b {
+                "void" => CPrimitive::Void,
+                "char" => {
+                    if unsigned_seen {
+                        CPrimitive::UChar
+                    } else if signed_seen {
+                        CPrimitive::SChar
+                    } else {
+                        CPrimitive::Char
+                    }
+                }
+                "short" => if unsigned_seen { CPrimitive::UShort } else { CPrimitive::Short },
+                "float" => CPrimitive::Float,
+                "double" => CPrimitive::Double,
+                "int" => {
+                    if long_count >= 2 {
+                        if unsigned_seen { CPrimitive::ULongLong } else { CPrimitive::LongLong }
+                    } else if long_count == 1 {
+                        if unsigned_seen { CPrimitive::ULong } else { CPrimitive::Long }
+                    } else if unsigned_seen {
+                        CPrimitive::UInt
+                    } else {
+                        CPrimitive::Int
+                    }
+                }
+                _ => unreachable!(),
+            }
+        } else if long_count > 0 {
+            if long_count >= 2 {
+                if unsigned_seen { CPrimitive::ULongLong } else { CPrimitive::LongLong }
+            } else if unsigned_seen {
+                CPrimitive::ULong
+            } else {
+                CPrimitive::Long
+            }
+        } else if signed_seen || unsigned_seen {
+            if unsigned_seen { CPrimitive::UInt } else { CPrimitive::Int }
+        } else {
+            // No keyword specifiers matched: this must be a typedef name
+            // (`size_t`, `uint32_t`, ...) or an unsupported type.
+            let name = self
+                .advance()
+                .ok_or_else(|| vm.new_value_error("cdef: expected a type name".to_string()))?;
+            return resolve_typedef(name)
+                .map(CTypeNode::Primitive)
+                .ok_or_else(|| vm.new_value_error(format!("cdef: unsupported type '{}'", name)));
+        };
+        Ok(CTypeNode::Primitive(primitive))
+    }
+
+    /// Applies any `*` pointer qualifiers following a type specifier.
+    fn parse_pointers(&mut self, mut node: CTypeNode) -> CTypeNode {
+        while self.peek() == Some("*") {
+            self.advance();
+            // A `const`/`volatile` right after `*` qualifies the pointer
+            // itself, not what it points to; still irrelevant to ctypes.
+            while matches!(self.peek(), Some("const") | Some("volatile")) {
+                self.advance();
+            }
+            node = CTypeNode::Pointer(Box::new(node));
+        }
+        node
+    }
+
+    /// Parses one parameter: a type specifier, pointer qualifiers, an
+    /// optional (and ignored) parameter name, and an optional `[N]`/`[]`
+    /// array suffix, which decays to a pointer per C's own parameter-
+    /// passing rules.
+    fn parse_param(&mut self, vm: &VirtualMachine) -> PyResult<CTypeNode> {
+        let base = self.parse_type_specifier(vm)?;
+        let mut node = self.parse_pointers(base);
+        if matches!(self.peek(), Some(tok) if tok != "," && tok != ")" && tok != "[") {
+            self.advance(); // unnamed parameter identifier, irrelevant to the type
+        }
+        while self.peek() == Some("[") {
+            self.advance();
+            if self.peek() == Some("]") {
+                self.advance();
+            } else {
+                let _len = self.advance(); // array length, unused once decayed
+                self.expect("]", vm)?;
+            }
+            node = CTypeNode::Pointer(Box::new(node));
+        }
+        Ok(node)
+    }
+
+    /// Parses a full `restype name(params...)` declaration.
+    fn parse_function(&mut self, vm: &VirtualMachine) -> PyResult<CFunctionNode> {
+        let restype = {
+            let base = self.parse_type_specifier(vm)?;
+            self.parse_pointers(base)
+        };
+        // The function name itself - ignored, the caller already knows
+        // which `PyCFuncPtr` this declaration is for.
+        if matches!(self.peek(), Some(tok) if tok != "(") {
+            self.advance();
+        }
+        self.expect("(", vm)?;
+
+        let mut params = Vec::new();
+        let mut variadic = false;
+        let is_void_only = self.peek() == Some("void")
+            && self.tokens.get(self.pos + 1).map(String::as_str) == Some(")");
+        if self.peek() == Some(")") || is_void_only {
+            if is_void_only {
+                self.advance();
+            }
+        } else {
+            loop {
+                if self.peek() == Some("...") {
+                    self.advance();
+                    variadic = true;
+                    break;
+                }
+                params.push(self.parse_param(vm)?);
+                if self.peek() == Some(",") {
+                    self.advance();
+                } else {
+                    break;
+                }
+            }
+        }
+        self.expect(")", vm)?;
+        if self.pos != self.tokens.len() {
+            return Err(vm.new_value_error(format!(
+                "cdef: unexpected trailing token '{}'",
+                self.tokens[self.pos]
+            )));
+        }
+        Ok(CFunctionNode { restype, params, variadic })
     }
 }
+
+/// Looks up a ctypes type object by name in the `ctypes` module's
+/// namespace - the same place a hand-written `cdef`-driven script would
+/// pull `c_int`/`c_char_p`/`POINTER`/etc. from.
+fn lookup_ctypes_attr(name: &str, vm: &VirtualMachine) -> PyResult<PyObjectRef> {
+    let module = vm.import("ctypes", None, 0)?;
+    module
+        .get_attr(name, vm)
+        .map_err(|_| vm.new_value_error(format!("cdef: ctypes has no attribute '{}'", name)))
+}
+
+/// Turns the parser's intermediate type graph into an actual ctypes type
+/// object: pointers resolve via `POINTER(...)`, with the `char *`/`void *`
+/// special cases ctypes itself special-cases as `c_char_p`/`c_void_p`, and
+/// arrays via the `elem_type * length` idiom ctypes uses to build `Array`
+/// subclasses.
+fn resolve_ctype_node(node: &CTypeNode, vm: &VirtualMachine) -> PyResult<PyObjectRef> {
+    match node {
+        CTypeNode::Primitive(CPrimitive::Void) => Ok(vm.ctx.none()),
+        CTypeNode::Primitive(prim) => lookup_ctypes_attr(prim.ctypes_name(), vm),
+        CTypeNode::Pointer(inner) => match inner.as_ref() {
+            CTypeNode::Primitive(CPrimitive::Char) => lookup_ctypes_attr("c_char_p", vm),
+            CTypeNode::Primitive(CPrimitive::Void) => lookup_ctypes_attr("c_void_p", vm),
+            _ => {
+                let inner_type = resolve_ctype_node(inner, vm)?;
+                let pointer_fn = lookup_ctypes_attr("POINTER", vm)?;
+                vm.invoke(&pointer_fn, (inner_type,))
+            }
+        },
+        CTypeNode::Array(inner, len) => {
+            let inner_type = resolve_ctype_node(inner, vm)?;
+            vm.call_method(&inner_type, "__mul__", (*len,))
+        }
+        CTypeNode::StructUnion(name) => lookup_ctypes_attr(name, vm),
+    }
+}
+
+/// Parses a C function prototype (e.g. `"int strcmp(const char *, const
+/// char *)"`) and fills in `func`'s `_restype_`/`_argtypes_` the way
+/// hand-writing them out would, mirroring cffi's `cdef()` workflow. A
+/// trailing `...` marks the prototype variadic; the variadic tail itself
+/// isn't recorded anywhere because `Function::call` already treats any
+/// runtime argument past `_argtypes_`'s length as a variadic trailing
+/// argument (see `chunk1-4`'s `PromotedType`/`CifRef` machinery above).
+pub fn apply_cdef(func: &PyCFuncPtr, declaration: &str, vm: &VirtualMachine) -> PyResult<()> {
+    let declaration = declaration.trim().trim_end_matches(';');
+    let tokens = cdef_tokenize(declaration);
+    let mut parser = CdefParser { tokens: &tokens, pos: 0 };
+    let parsed = parser.parse_function(vm)?;
+
+    let restype = resolve_ctype_node(&parsed.restype, vm)?;
+    *func._restype_.write() = if vm.is_none(&restype) { None } else { Some(restype) };
+
+    let argtypes = parsed
+        .params
+        .iter()
+        .map(|p| resolve_ctype_node(p, vm))
+        .collect::<PyResult<Vec<PyObjectRef>>>()?;
+    *func._argtypes_.write() = Some(vm.ctx.new_tuple(argtypes));
+
+    Ok(())
+}
+
+pub(super) fn init_types(vm: &VirtualMachine, module: &PyObjectRef) {
+    PyCFuncPtr::extend_class(&vm.ctx, PyCFuncPtr::static_type().as_ref());
+    PyCFuncTypeType::extend_class(&vm.ctx, PyCFuncTypeType::static_type().as_ref());
+    PyCallbackObject::extend_class(&vm.ctx, PyCallbackObject::static_type().as_ref());
+
+    let _ = module.set_attr(
+        "cdef",
+        vm.ctx.new_function(
+            "cdef",
+            |func: PyRef<PyCFuncPtr>, declaration: PyStrRef, vm: &VirtualMachine| -> PyResult<()> {
+                apply_cdef(&func, declaration.as_str(), vm)
+            },
+        ),
+        vm,
+    );
+
+    let _ = module.set_attr(
+        "CFUNCTYPE",
+        vm.ctx.new_function("CFUNCTYPE", |proto: FuncArgs, vm: &VirtualMachine| cfunctype(proto, vm)),
+        vm,
+    );
+
+    let _ = module.set_attr("get_errno", vm.ctx.new_function("get_errno", |vm: &VirtualMachine| get_errno(vm)), vm);
+    let _ = module.set_attr(
+        "set_errno",
+        vm.ctx.new_function("set_errno", |value: i32, vm: &VirtualMachine| set_errno(value, vm)),
+        vm,
+    );
+    let _ = module.set_attr("get_last_error", vm.ctx.new_function("get_last_error", |vm: &VirtualMachine| get_last_error(vm)), vm);
+    let _ = module.set_attr(
+        "set_last_error",
+        vm.ctx.new_function("set_last_error", |value: u32, vm: &VirtualMachine| set_last_error(value, vm)),
+        vm,
+    );
+}
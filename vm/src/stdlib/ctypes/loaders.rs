@@ -7,22 +7,305 @@ use crate::{PyObjectRef, PyResult, PyValue, pyclass};
 use libffi::middle::Abi;
 use once_cell::sync::Lazy;
 use std::collections::HashMap;
+use std::mem;
+use std::path::Path;
 // Assuming PyCFuncPtr will be accessible from super::function module
-use super::function::PyCFuncPtr; 
+use super::function::{PyCFuncPtr, default_c_abi};
 use libloading::Library;
 
-// TODO: Move LIBCACHE to a more appropriate location, possibly within the vm or a dedicated module.
-// For now, it's here to allow PyCDLL to access it.
-type LibCache = PyRwLock<HashMap<String, Library>>;
-static LIBCACHE: Lazy<LibCache> = Lazy::new(Default::default);
+/// A configurable, ordered search path for bare library names (`"m"`, `"ssl"`, ...),
+/// mirroring the classic `prepend_search_path` facility: directories are tried in order,
+/// each joined with the platform-decorated filename, before falling back to the bare name
+/// and letting the OS loader resolve it. Lets embedders sandbox which directories
+/// RustPython will `dlopen` from.
+static SEARCH_PATH: Lazy<PyRwLock<Vec<String>>> = Lazy::new(Default::default);
+
+pub(super) fn prepend_search_path(path: String) {
+    SEARCH_PATH.write().insert(0, path);
+}
+
+pub(super) fn append_search_path(path: String) {
+    SEARCH_PATH.write().push(path);
+}
+
+pub(super) fn reset_search_path() {
+    SEARCH_PATH.write().clear();
+}
+
+#[cfg(target_os = "macos")]
+fn platform_decorate(name: &str) -> String {
+    format!("lib{}.dylib", name)
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn platform_decorate(name: &str) -> String {
+    format!("lib{}.so", name)
+}
+
+#[cfg(windows)]
+fn platform_decorate(name: &str) -> String {
+    format!("{}.dll", name)
+}
+
+/// Apply the platform's library filename decoration (`lib{}.so`/`{}.dll`/`lib{}.dylib`)
+/// unless `name` already looks like a filename (i.e. its last path component has an
+/// extension), in which case it is passed through unchanged.
+fn decorate_name(name: &str) -> String {
+    let base = name.rsplit(['/', '\\']).next().unwrap_or(name);
+    if base.contains('.') {
+        name.to_string()
+    } else {
+        platform_decorate(name)
+    }
+}
+
+/// Resolve a bare library name (no path separators) against the configured search path,
+/// applying platform decoration, before falling back to the original name for the OS
+/// loader to resolve on its own (e.g. via the system's default search rules).
+fn resolve_search_path(name: &str) -> String {
+    if name.contains('/') || name.contains('\\') {
+        return name.to_string();
+    }
+    let decorated = decorate_name(name);
+    for dir in SEARCH_PATH.read().iter() {
+        let candidate = Path::new(dir).join(&decorated);
+        if candidate.exists() {
+            return candidate.to_string_lossy().into_owned();
+        }
+        let candidate = Path::new(dir).join(name);
+        if candidate.exists() {
+            return candidate.to_string_lossy().into_owned();
+        }
+    }
+    name.to_string()
+}
+
+// dlopen(3)/LoadLibraryExW mode flags, matching CPython's Modules/_ctypes/_ctypes.c.
+// glibc values are used as the canonical numbering; NetBSD/QNX/AIX historically assign
+// RTLD_GLOBAL/RTLD_LOCAL differently, but we only target the glibc numbering here.
+pub const RTLD_LAZY: i32 = 1;
+pub const RTLD_NOW: i32 = 2;
+pub const RTLD_GLOBAL: i32 = 0x100;
+pub const RTLD_LOCAL: i32 = 0x200;
+
+const DEFAULT_MODE: i32 = RTLD_NOW | RTLD_LOCAL;
+
+/// Identifies which library a `PyCFuncPtr` (or LIBCACHE entry) resolves symbols against:
+/// either a named, `dlopen`-d path, or the calling process itself (CPython's `CDLL(None)` /
+/// `ctypes.pythonapi` behavior, i.e. `dlopen(NULL, ...)`/`GetModuleHandle(NULL)`).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(super) enum LibRef {
+    Named(String),
+    SelfHandle,
+}
+
+impl std::fmt::Display for LibRef {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LibRef::Named(path) => write!(f, "{}", path),
+            LibRef::SelfHandle => write!(f, "<main program>"),
+        }
+    }
+}
+
+// Keyed on (library, flags): a path loaded RTLD_LOCAL and RTLD_GLOBAL are distinct handles,
+// so the flags must be part of the cache key rather than keying on the library alone.
+pub(super) type LibKey = (LibRef, i32);
+
+/// A cached, loaded library plus the bookkeeping needed to actually unload it: the raw OS
+/// handle (exposed to Python as `_handle`) and a count of the live `PyCDLL`/`PyWinDLL`/...
+/// objects referencing it. The `Library` is only dropped (calling `dlclose`/`FreeLibrary`)
+/// once the last such object goes away, rather than being leaked for the process lifetime.
+pub(super) struct LibEntry {
+    pub(super) library: Library,
+    handle: usize,
+    refcount: usize,
+    // Whether this entry's `Library` actually owns `handle`, i.e. was opened by us via
+    // `dlopen`/`LoadLibrary` rather than adopted from a caller-supplied `handle=` (CPython's
+    // `CDLL(name, handle=...)`). An adopted handle is never ours to close - whoever opened it
+    // still owns it and may still be using it - so `library` must be forgotten rather than
+    // dropped when a non-owning entry is evicted, or we'd `dlclose`/`FreeLibrary` a handle
+    // ctypes never opened.
+    owning: bool,
+}
+
+/// Drop `entry`'s `Library` the way its `owning` flag demands: actually unload it if we
+/// opened it ourselves, or just forget the wrapper (leaving the real handle alone) if it was
+/// adopted from a caller-supplied `handle=`.
+fn drop_lib_entry(entry: LibEntry) {
+    if entry.owning {
+        drop(entry.library);
+    } else {
+        mem::forget(entry.library);
+    }
+}
+
+type LibCache = PyRwLock<HashMap<LibKey, LibEntry>>;
+pub(super) static LIBCACHE: Lazy<LibCache> = Lazy::new(Default::default);
+
+#[cfg(unix)]
+unsafe fn open_with_mode(library_path: &str, mode: i32) -> Result<(Library, usize), String> {
+    use libloading::os::unix::Library as UnixLibrary;
+    let lib = UnixLibrary::open(Some(library_path), mode).map_err(|e| e.to_string())?;
+    // `into_raw` hands back the raw `void*` handle `dlopen` returned, which is what CPython
+    // exposes as `_handle` and what `dlclose` needs; wrap it straight back up so `lib` stays
+    // a live, droppable `Library` instead of being consumed.
+    let handle = lib.into_raw() as usize;
+    Ok((Library::from(UnixLibrary::from_raw(handle as *mut _)), handle))
+}
+
+#[cfg(windows)]
+unsafe fn open_with_mode(library_path: &str, mode: i32) -> Result<(Library, usize), String> {
+    use libloading::os::windows::Library as WindowsLibrary;
+    // mode here is treated as the dwFlags argument to LoadLibraryExW.
+    let lib = WindowsLibrary::load_with_flags(library_path, mode as u32).map_err(|e| e.to_string())?;
+    let handle = lib.into_raw() as usize;
+    Ok((Library::from(WindowsLibrary::from_raw(handle as _)), handle))
+}
+
+#[cfg(unix)]
+unsafe fn open_self() -> Result<(Library, usize), String> {
+    use libloading::os::unix::Library as UnixLibrary;
+    let lib = UnixLibrary::this().map_err(|e| e.to_string())?;
+    let handle = lib.into_raw() as usize;
+    Ok((Library::from(UnixLibrary::from_raw(handle as *mut _)), handle))
+}
+
+#[cfg(windows)]
+unsafe fn open_self() -> Result<(Library, usize), String> {
+    use libloading::os::windows::Library as WindowsLibrary;
+    let lib = WindowsLibrary::this().map_err(|e| e.to_string())?;
+    let handle = lib.into_raw() as usize;
+    Ok((Library::from(WindowsLibrary::from_raw(handle as _)), handle))
+}
+
+#[cfg(unix)]
+unsafe fn library_from_raw_handle(raw: usize) -> Library {
+    use libloading::os::unix::Library as UnixLibrary;
+    Library::from(UnixLibrary::from_raw(raw as *mut _))
+}
+
+#[cfg(windows)]
+unsafe fn library_from_raw_handle(raw: usize) -> Library {
+    use libloading::os::windows::Library as WindowsLibrary;
+    Library::from(WindowsLibrary::from_raw(raw as _))
+}
+
+/// Load (or reuse, bumping its refcount) the library identified by `lib_ref`/`mode`, or, if
+/// `handle` is given, adopt that already-open OS handle instead of calling `dlopen` ourselves
+/// (CPython's `CDLL(name, handle=...)`). Returns the raw handle for `_handle`.
+fn acquire_library(
+    lib_ref: &LibRef,
+    mode: i32,
+    handle: Option<usize>,
+    vm: &VirtualMachine,
+) -> PyResult<usize> {
+    let key: LibKey = (lib_ref.clone(), mode);
+    let mut lib_cache_guard = LIBCACHE.write();
+    if let Some(entry) = lib_cache_guard.get_mut(&key) {
+        entry.refcount += 1;
+        return Ok(entry.handle);
+    }
+
+    let owning = handle.is_none();
+    let (library, raw_handle) = if let Some(raw) = handle {
+        (unsafe { library_from_raw_handle(raw) }, raw)
+    } else {
+        let opened = match lib_ref {
+            LibRef::Named(path) => unsafe { open_with_mode(path, mode) },
+            LibRef::SelfHandle => unsafe { open_self() },
+        };
+        opened.map_err(|e| vm.new_os_error(format!("Failed to load library '{}': {}", lib_ref, e)))?
+    };
+
+    lib_cache_guard.insert(
+        key,
+        LibEntry {
+            library,
+            handle: raw_handle,
+            refcount: 1,
+            owning,
+        },
+    );
+    Ok(raw_handle)
+}
+
+/// Drop a `PyCDLL`/`PyWinDLL`/... object's reference to its library; once the last
+/// reference is released the `Library` is dropped, actually calling `dlclose`/`FreeLibrary`
+/// instead of leaking it for the process lifetime.
+fn release_library(lib_ref: &LibRef, mode: i32) {
+    let key: LibKey = (lib_ref.clone(), mode);
+    let mut lib_cache_guard = LIBCACHE.write();
+    if let Some(entry) = lib_cache_guard.get_mut(&key) {
+        entry.refcount -= 1;
+        if entry.refcount == 0 {
+            if let Some(entry) = lib_cache_guard.remove(&key) {
+                drop_lib_entry(entry);
+            }
+        }
+    }
+}
+
+/// `_ctypes` module-level `dlclose`-equivalent: force-unloads a library by its `_handle`
+/// value regardless of its remaining refcount, matching the low-level escape hatch CPython
+/// exposes alongside the automatic `CDLL.__del__` unloading.
+fn dlclose(handle: usize, _vm: &VirtualMachine) -> bool {
+    let mut lib_cache_guard = LIBCACHE.write();
+    let key = lib_cache_guard
+        .iter()
+        .find(|(_, entry)| entry.handle == handle)
+        .map(|(key, _)| key.clone());
+    match key {
+        Some(key) => {
+            if let Some(entry) = lib_cache_guard.remove(&key) {
+                drop_lib_entry(entry);
+            }
+            true
+        }
+        None => false,
+    }
+}
+
+/// Resolve the `name` argument accepted by every DLL constructor: a string path, or `None`
+/// to get a handle to the running process (CPython's `CDLL(None)` / `ctypes.pythonapi`).
+fn resolve_lib_ref(name: PyObjectRef, vm: &VirtualMachine) -> PyResult<LibRef> {
+    if vm.is_none(&name) {
+        Ok(LibRef::SelfHandle)
+    } else {
+        let name = name
+            .downcast::<crate::builtins::PyStr>()
+            .map_err(|_| vm.new_type_error("name must be a string or None".to_owned()))?;
+        Ok(LibRef::Named(resolve_search_path(name.as_str())))
+    }
+}
+
+/// Resolve the optional `handle` argument (an int, from `_handle` of a sibling DLL object or
+/// another loader) into a raw OS handle to adopt instead of calling `dlopen` ourselves.
+fn resolve_handle_arg(handle: OptionalArg<PyObjectRef>, vm: &VirtualMachine) -> PyResult<Option<usize>> {
+    match handle {
+        OptionalArg::Present(obj) if !vm.is_none(&obj) => {
+            let value = obj
+                .downcast::<crate::builtins::PyInt>()
+                .map_err(|_| vm.new_type_error("handle must be an int".to_owned()))?;
+            let handle = num_traits::ToPrimitive::to_usize(value.as_bigint())
+                .ok_or_else(|| vm.new_value_error("handle out of range".to_owned()))?;
+            Ok(Some(handle))
+        }
+        _ => Ok(None),
+    }
+}
 
 #[pyclass(name = "CDLL", module = "_ctypes")]
 #[derive(Debug)]
 pub struct PyCDLL {
-    // Store the name for now, as PyObjectRef cannot directly hold a Library.
-    // We'll use this name to retrieve the Library from LIBCACHE when needed.
-    library_name: String, 
+    // Store the reference for now, as PyObjectRef cannot directly hold a Library.
+    // We'll use this to retrieve the Library from LIBCACHE when needed.
+    library_ref: LibRef,
+    mode: i32,
     default_abi: Abi,
+    use_errno: bool,
+    use_last_error: bool,
+    handle: usize,
 }
 
 #[pyclass]
@@ -30,42 +313,35 @@ impl PyCDLL {
     #[pyslot]
     fn py_new(
         cls: PyTypeRef,
-        name: PyStrRef,
-        mode: OptionalArg<i32>, // mode is for dlopen flags, unused for now
-        handle: OptionalArg<PyObjectRef>, // handle allows using an already opened library, unused for now
-        use_errno: OptionalArg<bool>, // For POSIX, copies errno, unused for now
-        use_last_error: OptionalArg<bool>, // For Windows, copies GetLastError, unused for now
+        name: PyObjectRef, // a path, or None for a handle to the calling process
+        mode: OptionalArg<i32>, // dlopen/LoadLibrary flags; defaults to RTLD_NOW | RTLD_LOCAL
+        handle: OptionalArg<PyObjectRef>, // use an already-opened library (its raw handle)
+        use_errno: OptionalArg<bool>, // For POSIX, swap a private errno around each call
+        use_last_error: OptionalArg<bool>, // For Windows, swap a private GetLastError around each call
         vm: &VirtualMachine,
     ) -> PyResult<Self> {
-        let library_path = name.as_str();
-        
-        // Simplified library loading:
-        // - Ignores mode, handle, use_errno, use_last_error for now.
-        // - Error handling needs to be more robust.
-        let mut lib_cache_guard = LIBCACHE.write();
-        if !lib_cache_guard.contains_key(library_path) {
-            match unsafe { Library::new(library_path) } {
-                Ok(lib) => {
-                    lib_cache_guard.insert(library_path.to_string(), lib);
-                }
-                Err(e) => return Err(vm.new_os_error(format!("Failed to load library '{}': {}", library_path, e))),
-            }
-        }
-        // Drop the write guard soon as possible
-        drop(lib_cache_guard);
+        let library_ref = resolve_lib_ref(name, vm)?;
+        let flags = mode.unwrap_or(DEFAULT_MODE);
+        let raw_handle = resolve_handle_arg(handle, vm)?;
+        let handle = acquire_library(&library_ref, flags, raw_handle, vm)?;
 
         Ok(PyCDLL {
-            library_name: library_path.to_string(),
-            default_abi: Abi::Cdecl,
+            library_ref,
+            mode: flags,
+            default_abi: default_c_abi(),
+            use_errno: use_errno.unwrap_or(false),
+            use_last_error: use_last_error.unwrap_or(false),
+            handle,
         })
     }
 
     #[pymethod]
     fn __getattr__(&self, name: PyStrRef, vm: &VirtualMachine) -> PyResult {
         // Check if the library is still loaded
+        let key: LibKey = (self.library_ref.clone(), self.mode);
         let lib_cache_guard = LIBCACHE.read();
-        let _library = lib_cache_guard.get(&self.library_name)
-            .ok_or_else(|| vm.new_os_error(format!("Library {} not found in cache or unloaded", self.library_name)))?;
+        let _library = lib_cache_guard.get(&key)
+            .ok_or_else(|| vm.new_os_error(format!("Library {} not found in cache or unloaded", self.library_ref)))?;
         // Drop the read guard
         drop(lib_cache_guard);
 
@@ -74,13 +350,27 @@ impl PyCDLL {
         // and the ABI.
         // For now, we'll assume PyCFuncPtr::new can take these.
         // The actual Symbol<T> loading will happen within PyCFuncPtr when it's called.
-        PyCFuncPtr::new_for_dll(
+        PyCFuncPtr::new_for_dll_with_errno(
             name.to_owned(), // function name
-            self.library_name.clone(), // library name/identifier
+            self.library_ref.clone(), // library identifier
+            self.mode, // flags the library was loaded with (part of the LIBCACHE key)
             self.default_abi, // calling convention
+            self.use_errno,
+            self.use_last_error,
             vm,
         )
     }
+
+    #[pygetset(name = "_handle")]
+    fn handle(&self, vm: &VirtualMachine) -> PyObjectRef {
+        vm.ctx.new_int(self.handle).into()
+    }
+}
+
+impl Drop for PyCDLL {
+    fn drop(&mut self) {
+        release_library(&self.library_ref, self.mode);
+    }
 }
 
 impl PyTpGetattro for PyCDLL {
@@ -95,12 +385,45 @@ pub(super) fn init_type(vm: &VirtualMachine, module: &PyObjectRef, typ: &PyTypeR
     PyWinDLL::extend_class(&vm.ctx, typ);
     PyOleDLL::extend_class(&vm.ctx, typ);
     PyPyDLL::extend_class(&vm.ctx, typ);
-    // Any other type specific initializations for PyCDLL
+
+    // Expose the RTLD_* mode constants so callers can compose `mode` the same way
+    // CPython's ctypes does (e.g. `mode=RTLD_GLOBAL`).
+    let _ = module.set_attr("RTLD_LAZY", vm.ctx.new_int(RTLD_LAZY), vm);
+    let _ = module.set_attr("RTLD_NOW", vm.ctx.new_int(RTLD_NOW), vm);
+    let _ = module.set_attr("RTLD_GLOBAL", vm.ctx.new_int(RTLD_GLOBAL), vm);
+    let _ = module.set_attr("RTLD_LOCAL", vm.ctx.new_int(RTLD_LOCAL), vm);
+    let _ = module.set_attr(
+        "dlclose",
+        vm.ctx.new_function("dlclose", |handle: usize, vm: &VirtualMachine| dlclose(handle, vm)),
+        vm,
+    );
+
+    // A configurable search path for bare library names, in the spirit of
+    // `prepend_search_path` on the classic dynamic-library wrappers.
+    let _ = module.set_attr(
+        "prepend_search_path",
+        vm.ctx.new_function("prepend_search_path", |path: PyStrRef, _vm: &VirtualMachine| {
+            prepend_search_path(path.as_str().to_owned())
+        }),
+        vm,
+    );
+    let _ = module.set_attr(
+        "append_search_path",
+        vm.ctx.new_function("append_search_path", |path: PyStrRef, _vm: &VirtualMachine| {
+            append_search_path(path.as_str().to_owned())
+        }),
+        vm,
+    );
+    let _ = module.set_attr(
+        "reset_search_path",
+        vm.ctx.new_function("reset_search_path", |_vm: &VirtualMachine| reset_search_path()),
+        vm,
+    );
 }
 
 // This function is not strictly necessary if init_type is used by make_module,
 // but can be kept if direct access to PyCDLL type is needed elsewhere.
-pub fn make_ ctypes_cdll_type(ctx: &crate::Context) -> PyTypeRef {
+pub fn make_ctypes_cdll_type(ctx: &crate::Context) -> PyTypeRef {
     PyCDLL::class_with_opts(ctx, crate::builtins::PyType::static_type())
 }
 
@@ -108,8 +431,12 @@ pub fn make_ ctypes_cdll_type(ctx: &crate::Context) -> PyTypeRef {
 #[pyclass(name = "WinDLL", module = "_ctypes")]
 #[derive(Debug)]
 pub struct PyWinDLL {
-    library_name: String,
+    library_ref: LibRef,
+    mode: i32,
     default_abi: Abi,
+    use_errno: bool,
+    use_last_error: bool,
+    handle: usize,
 }
 
 #[pyclass]
@@ -117,45 +444,57 @@ impl PyWinDLL {
     #[pyslot]
     fn py_new(
         cls: PyTypeRef,
-        name: PyStrRef,
+        name: PyObjectRef,
         mode: OptionalArg<i32>,
         handle: OptionalArg<PyObjectRef>,
         use_errno: OptionalArg<bool>,
         use_last_error: OptionalArg<bool>,
         vm: &VirtualMachine,
     ) -> PyResult<Self> {
-        let library_path = name.as_str();
-        let mut lib_cache_guard = LIBCACHE.write();
-        if !lib_cache_guard.contains_key(library_path) {
-            match unsafe { Library::new(library_path) } {
-                Ok(lib) => {
-                    lib_cache_guard.insert(library_path.to_string(), lib);
-                }
-                Err(e) => return Err(vm.new_os_error(format!("Failed to load library '{}': {}", library_path, e))),
-            }
-        }
-        drop(lib_cache_guard);
+        let library_ref = resolve_lib_ref(name, vm)?;
+        let flags = mode.unwrap_or(DEFAULT_MODE);
+        let raw_handle = resolve_handle_arg(handle, vm)?;
+        let handle = acquire_library(&library_ref, flags, raw_handle, vm)?;
 
         Ok(PyWinDLL {
-            library_name: library_path.to_string(),
+            library_ref,
+            mode: flags,
             default_abi: Abi::Stdcall, // Key difference for WinDLL
+            use_errno: use_errno.unwrap_or(false),
+            use_last_error: use_last_error.unwrap_or(false),
+            handle,
         })
     }
 
     #[pymethod]
     fn __getattr__(&self, name: PyStrRef, vm: &VirtualMachine) -> PyResult {
+        let key: LibKey = (self.library_ref.clone(), self.mode);
         let lib_cache_guard = LIBCACHE.read();
-        let _library = lib_cache_guard.get(&self.library_name)
-            .ok_or_else(|| vm.new_os_error(format!("Library {} not found in cache or unloaded", self.library_name)))?;
+        let _library = lib_cache_guard.get(&key)
+            .ok_or_else(|| vm.new_os_error(format!("Library {} not found in cache or unloaded", self.library_ref)))?;
         drop(lib_cache_guard);
 
-        PyCFuncPtr::new_for_dll(
+        PyCFuncPtr::new_for_dll_with_errno(
             name.to_owned(),
-            self.library_name.clone(),
+            self.library_ref.clone(),
+            self.mode,
             self.default_abi, // Use Stdcall
+            self.use_errno,
+            self.use_last_error,
             vm,
         )
     }
+
+    #[pygetset(name = "_handle")]
+    fn handle(&self, vm: &VirtualMachine) -> PyObjectRef {
+        vm.ctx.new_int(self.handle).into()
+    }
+}
+
+impl Drop for PyWinDLL {
+    fn drop(&mut self) {
+        release_library(&self.library_ref, self.mode);
+    }
 }
 
 impl PyTpGetattro for PyWinDLL {
@@ -164,7 +503,7 @@ impl PyTpGetattro for PyWinDLL {
     }
 }
 
-pub fn make_ ctypes_windll_type(ctx: &crate::Context) -> PyTypeRef {
+pub fn make_ctypes_windll_type(ctx: &crate::Context) -> PyTypeRef {
     PyWinDLL::class_with_opts(ctx, crate::builtins::PyType::static_type())
 }
 
@@ -173,8 +512,12 @@ pub fn make_ ctypes_windll_type(ctx: &crate::Context) -> PyTypeRef {
 #[pyclass(name = "OleDLL", module = "_ctypes")]
 #[derive(Debug)]
 pub struct PyOleDLL {
-    library_name: String,
+    library_ref: LibRef,
+    mode: i32,
     default_abi: Abi,
+    use_errno: bool,
+    use_last_error: bool,
+    handle: usize,
 }
 
 #[pyclass]
@@ -182,45 +525,57 @@ impl PyOleDLL {
     #[pyslot]
     fn py_new(
         cls: PyTypeRef,
-        name: PyStrRef,
+        name: PyObjectRef,
         mode: OptionalArg<i32>,
         handle: OptionalArg<PyObjectRef>,
         use_errno: OptionalArg<bool>,
         use_last_error: OptionalArg<bool>,
         vm: &VirtualMachine,
     ) -> PyResult<Self> {
-        let library_path = name.as_str();
-        let mut lib_cache_guard = LIBCACHE.write();
-        if !lib_cache_guard.contains_key(library_path) {
-            match unsafe { Library::new(library_path) } {
-                Ok(lib) => {
-                    lib_cache_guard.insert(library_path.to_string(), lib);
-                }
-                Err(e) => return Err(vm.new_os_error(format!("Failed to load library '{}': {}", library_path, e))),
-            }
-        }
-        drop(lib_cache_guard);
+        let library_ref = resolve_lib_ref(name, vm)?;
+        let flags = mode.unwrap_or(DEFAULT_MODE);
+        let raw_handle = resolve_handle_arg(handle, vm)?;
+        let handle = acquire_library(&library_ref, flags, raw_handle, vm)?;
 
         Ok(PyOleDLL {
-            library_name: library_path.to_string(),
+            library_ref,
+            mode: flags,
             default_abi: Abi::Stdcall, // Key difference for OleDLL
+            use_errno: use_errno.unwrap_or(false),
+            use_last_error: use_last_error.unwrap_or(false),
+            handle,
         })
     }
 
     #[pymethod]
     fn __getattr__(&self, name: PyStrRef, vm: &VirtualMachine) -> PyResult {
+        let key: LibKey = (self.library_ref.clone(), self.mode);
         let lib_cache_guard = LIBCACHE.read();
-        let _library = lib_cache_guard.get(&self.library_name)
-            .ok_or_else(|| vm.new_os_error(format!("Library {} not found in cache or unloaded", self.library_name)))?;
+        let _library = lib_cache_guard.get(&key)
+            .ok_or_else(|| vm.new_os_error(format!("Library {} not found in cache or unloaded", self.library_ref)))?;
         drop(lib_cache_guard);
 
-        PyCFuncPtr::new_for_dll(
+        PyCFuncPtr::new_for_dll_with_errno(
             name.to_owned(),
-            self.library_name.clone(),
+            self.library_ref.clone(),
+            self.mode,
             self.default_abi, // Use Stdcall
+            self.use_errno,
+            self.use_last_error,
             vm,
         )
     }
+
+    #[pygetset(name = "_handle")]
+    fn handle(&self, vm: &VirtualMachine) -> PyObjectRef {
+        vm.ctx.new_int(self.handle).into()
+    }
+}
+
+impl Drop for PyOleDLL {
+    fn drop(&mut self) {
+        release_library(&self.library_ref, self.mode);
+    }
 }
 
 impl PyTpGetattro for PyOleDLL {
@@ -229,7 +584,7 @@ impl PyTpGetattro for PyOleDLL {
     }
 }
 
-pub fn make_ ctypes_oledll_type(ctx: &crate::Context) -> PyTypeRef {
+pub fn make_ctypes_oledll_type(ctx: &crate::Context) -> PyTypeRef {
     PyOleDLL::class_with_opts(ctx, crate::builtins::PyType::static_type())
 }
 
@@ -238,8 +593,12 @@ pub fn make_ ctypes_oledll_type(ctx: &crate::Context) -> PyTypeRef {
 #[pyclass(name = "PyDLL", module = "_ctypes")]
 #[derive(Debug)]
 pub struct PyPyDLL {
-    library_name: String,
+    library_ref: LibRef,
+    mode: i32,
     default_abi: Abi,
+    use_errno: bool,
+    use_last_error: bool,
+    handle: usize,
 }
 
 #[pyclass]
@@ -247,7 +606,7 @@ impl PyPyDLL {
     #[pyslot]
     fn py_new(
         cls: PyTypeRef,
-        name: PyStrRef,
+        name: PyObjectRef,
         mode: OptionalArg<i32>,
         handle: OptionalArg<PyObjectRef>,
         // PyDLL doesn't use use_errno or use_last_error in CPython _ctypes.c
@@ -256,38 +615,50 @@ impl PyPyDLL {
         _use_last_error: OptionalArg<bool>,
         vm: &VirtualMachine,
     ) -> PyResult<Self> {
-        let library_path = name.as_str();
-        let mut lib_cache_guard = LIBCACHE.write();
-        if !lib_cache_guard.contains_key(library_path) {
-            match unsafe { Library::new(library_path) } {
-                Ok(lib) => {
-                    lib_cache_guard.insert(library_path.to_string(), lib);
-                }
-                Err(e) => return Err(vm.new_os_error(format!("Failed to load library '{}': {}", library_path, e))),
-            }
-        }
-        drop(lib_cache_guard);
+        let library_ref = resolve_lib_ref(name, vm)?;
+        let flags = mode.unwrap_or(DEFAULT_MODE);
+        let raw_handle = resolve_handle_arg(handle, vm)?;
+        let handle = acquire_library(&library_ref, flags, raw_handle, vm)?;
 
         Ok(PyPyDLL {
-            library_name: library_path.to_string(),
-            default_abi: Abi::Cdecl, // Key difference for PyDLL
+            library_ref,
+            mode: flags,
+            default_abi: default_c_abi(), // Same default convention as CDLL
+            use_errno: _use_errno.unwrap_or(false),
+            use_last_error: _use_last_error.unwrap_or(false),
+            handle,
         })
     }
 
     #[pymethod]
     fn __getattr__(&self, name: PyStrRef, vm: &VirtualMachine) -> PyResult {
+        let key: LibKey = (self.library_ref.clone(), self.mode);
         let lib_cache_guard = LIBCACHE.read();
-        let _library = lib_cache_guard.get(&self.library_name)
-            .ok_or_else(|| vm.new_os_error(format!("Library {} not found in cache or unloaded", self.library_name)))?;
+        let _library = lib_cache_guard.get(&key)
+            .ok_or_else(|| vm.new_os_error(format!("Library {} not found in cache or unloaded", self.library_ref)))?;
         drop(lib_cache_guard);
 
-        PyCFuncPtr::new_for_dll(
+        PyCFuncPtr::new_for_dll_with_errno(
             name.to_owned(),
-            self.library_name.clone(),
+            self.library_ref.clone(),
+            self.mode,
             self.default_abi, // Use Cdecl
+            self.use_errno,
+            self.use_last_error,
             vm,
         )
     }
+
+    #[pygetset(name = "_handle")]
+    fn handle(&self, vm: &VirtualMachine) -> PyObjectRef {
+        vm.ctx.new_int(self.handle).into()
+    }
+}
+
+impl Drop for PyPyDLL {
+    fn drop(&mut self) {
+        release_library(&self.library_ref, self.mode);
+    }
 }
 
 impl PyTpGetattro for PyPyDLL {
@@ -296,6 +667,6 @@ impl PyTpGetattro for PyPyDLL {
     }
 }
 
-pub fn make_ ctypes_pydll_type(ctx: &crate::Context) -> PyTypeRef {
+pub fn make_ctypes_pydll_type(ctx: &crate::Context) -> PyTypeRef {
     PyPyDLL::class_with_opts(ctx, crate::builtins::PyType::static_type())
 }